@@ -1,16 +1,71 @@
 use bevy::{
-    prelude::{Camera, Event, EventReader, Query, Res, ResMut, Resource, Transform, Vec3, With},
+    prelude::{
+        Camera, DetectChanges, Event, EventReader, GlobalTransform, Query, Res, ResMut, Resource,
+        Transform, Vec2, Vec3, With,
+    },
     time::Time,
+    ui::UiScale,
 };
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{combat::PlayfieldBounds, settings::Settings};
+
+// Presets for where the camera sits above the playfield, from the classic near top-down view
+// to a more angled, cinematic one
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum CameraView {
+    Classic,
+    Angled,
+    Cinematic,
+}
+
+impl CameraView {
+    pub fn next(&self) -> CameraView {
+        match self {
+            CameraView::Classic => CameraView::Angled,
+            CameraView::Angled => CameraView::Cinematic,
+            CameraView::Cinematic => CameraView::Classic,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CameraView::Classic => "Classic",
+            CameraView::Angled => "Angled",
+            CameraView::Cinematic => "Cinematic",
+        }
+    }
+
+    pub fn position(&self) -> Vec3 {
+        match self {
+            CameraView::Classic => Vec3::new(0.0, 20.0, 2.0),
+            CameraView::Angled => Vec3::new(0.0, 18.0, 7.0),
+            CameraView::Cinematic => Vec3::new(0.0, 14.0, 11.0),
+        }
+    }
+
+    pub fn look_target(&self) -> Vec3 {
+        Vec3::ZERO
+    }
+}
 
 #[derive(Resource)]
 pub struct CameraState {
+    pub view: CameraView,
+    // Where the camera rests when it isn't shaking, follows the chosen view
     pub original_position: Vec3,
+    pub look_target: Vec3,
     pub shake_duration: f32,
     pub shake_intensity: f32,
 }
 
+impl CameraState {
+    pub fn rest_transform(&self) -> Transform {
+        Transform::from_translation(self.original_position).looking_at(self.look_target, Vec3::Y)
+    }
+}
+
 #[derive(Event)]
 pub struct CameraShakeEvent {
     pub intensity: f32,
@@ -18,6 +73,7 @@ pub struct CameraShakeEvent {
 
 pub fn on_hit_camera_shake(
     mut camera_state: ResMut<CameraState>,
+    settings: Res<Settings>,
     time: Res<Time>,
     mut er: EventReader<CameraShakeEvent>,
     mut camera: Query<&mut Transform, With<Camera>>,
@@ -31,9 +87,10 @@ pub fn on_hit_camera_shake(
         } else {
             let mut rng = rand::thread_rng();
 
-            // Shake!
-            let rand_x = rng.gen_range(-0.1..0.1) * camera_state.shake_intensity;
-            let rand_z = rng.gen_range(-0.1..0.1) * camera_state.shake_intensity;
+            // Shake! Scaled down, or turned off entirely, by the accessibility setting
+            let intensity = camera_state.shake_intensity * settings.shake_scale.clamp(0.0, 1.0);
+            let rand_x = rng.gen_range(-0.1..0.1) * intensity;
+            let rand_z = rng.gen_range(-0.1..0.1) * intensity;
             transform.translation.x += rand_x;
             transform.translation.z += rand_z;
         }
@@ -49,10 +106,76 @@ pub fn on_hit_camera_shake(
     }
 }
 
+// Where a point in the world shows up in the UI. Bevy multiplies `Val::Px` by the UI scale, so
+// the position on the viewport is divided by it to stay lined up with the world
+pub fn world_to_ui_position(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    ui_scale: &UiScale,
+    position: Vec3,
+) -> Option<Vec2> {
+    let viewport_position = camera.world_to_viewport(camera_transform, position)?;
+    Some(viewport_position / ui_scale.scale as f32)
+}
+
+// Fits the playfield bounds to what the camera actually sees of the y = 0 plane, measured
+// across the middle of the screen, so they follow resolution and camera changes
+pub fn update_playfield_bounds(
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut bounds: ResMut<PlayfieldBounds>,
+) {
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    let Some(viewport_size) = camera.logical_viewport_size() else {
+        return;
+    };
+
+    let edge_on_plane = |x: f32| {
+        let ray =
+            camera.viewport_to_world(camera_transform, Vec2::new(x, viewport_size.y / 2.0))?;
+        let distance = ray.intersect_plane(Vec3::ZERO, Vec3::Y)?;
+        Some(ray.get_point(distance))
+    };
+    let (Some(left), Some(right)) = (edge_on_plane(0.0), edge_on_plane(viewport_size.x)) else {
+        return;
+    };
+
+    let half_width = (right.x - left.x).abs() / 2.0;
+    // Avoid flagging the resource as changed every frame
+    if (bounds.half_width - half_width).abs() > 0.01 {
+        bounds.half_width = half_width;
+    }
+}
+
+// Moves the camera when the view is changed in the options, the playfield bounds are measured
+// from the camera so they follow along by themselves
+pub fn apply_camera_view(
+    settings: Res<Settings>,
+    mut camera_state: ResMut<CameraState>,
+    mut camera: Query<&mut Transform, With<Camera>>,
+) {
+    if !settings.is_changed() || camera_state.view == settings.camera_view {
+        return;
+    }
+
+    camera_state.view = settings.camera_view;
+    camera_state.original_position = settings.camera_view.position();
+    camera_state.look_target = settings.camera_view.look_target();
+    camera_state.shake_duration = 0.;
+    camera_state.shake_intensity = 0.;
+    if let Ok(mut transform) = camera.get_single_mut() {
+        *transform = camera_state.rest_transform();
+    }
+}
+
 impl Default for CameraState {
     fn default() -> Self {
+        let view = CameraView::Classic;
         Self {
-            original_position: Vec3::new(0.0, 20.0, 2.0),
+            view,
+            original_position: view.position(),
+            look_target: view.look_target(),
             shake_duration: 0.,
             shake_intensity: 0.,
         }
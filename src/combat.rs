@@ -1,12 +1,28 @@
 use bevy::{
     prelude::{
-        shape, Assets, BuildChildren, Color, Commands, Component, Mesh, PbrBundle, Quat, ResMut,
-        SpatialBundle, StandardMaterial, Transform, Vec3,
+        shape, AlphaMode, Assets, BuildChildren, Commands, Component, Entity, Event, Handle, Mesh,
+        PbrBundle, Quat, Res, ResMut, Resource, SpatialBundle, StandardMaterial, Transform, Vec3,
     },
+    time::Time,
     transform::TransformBundle,
 };
 use bevy_rapier3d::prelude::{ActiveEvents, Collider, Sensor};
 
+use crate::theme::Theme;
+
+// Player bullets travel up the screen, towards -z, and enemy bullets down towards +z
+pub const PLAYER_BULLET_DIRECTION: Vec3 = Vec3::NEG_Z;
+pub const ENEMY_BULLET_DIRECTION: Vec3 = Vec3::Z;
+
+// Longest step the gameplay movement takes in one frame, so that a hitch or alt-tab doesn't
+// make bullets and ships teleport across the playfield
+pub const MAX_DELTA_S: f32 = 1.0 / 30.0;
+
+// A faint, stretched copy of the bullet drawn behind it so fast bullets are easier to track
+const BULLET_TRAIL_LENGTH: f32 = 0.35;
+const BULLET_TRAIL_ALPHA: f32 = 0.35;
+const BULLET_TRAIL_GLOW: f32 = 0.5;
+
 #[derive(Component)]
 pub struct EntityDeath {
     pub position: Vec3,  // Where the death occured
@@ -19,6 +35,9 @@ pub struct ParticleHitEffect {
     pub is_large: bool,
 }
 
+#[derive(Event)]
+pub struct PlayerHitEvent {}
+
 #[derive(Component)]
 pub struct SmallHitEffect {}
 
@@ -28,38 +47,163 @@ pub struct LargeHitEffect {}
 #[derive(Component)]
 pub struct Damageable {
     pub health: u32,
+    pub max_health: u32,
     pub is_player: bool,
 }
 
 #[derive(Component)]
 pub struct Bullet {
     pub is_player_bullet: bool,
-    pub up_direction: bool,
+    pub direction: Vec3, // Normalized
     pub velocity: f32,
     pub damage: u32,
 }
 
+// The faint ghost drawn behind a bullet, see `spawn_bullet`
+#[derive(Component)]
+pub struct BulletTrail {}
+
+// Every trail looks the same for its side, so the mesh and materials are shared by all bullets
+#[derive(Resource)]
+pub struct BulletTrailAssets {
+    pub mesh: Handle<Mesh>,
+    pub player_material: Handle<StandardMaterial>,
+    pub enemy_material: Handle<StandardMaterial>,
+}
+
+impl BulletTrailAssets {
+    pub fn material(&self, is_player_bullet: bool) -> &Handle<StandardMaterial> {
+        if is_player_bullet {
+            &self.player_material
+        } else {
+            &self.enemy_material
+        }
+    }
+}
+
+// Bullets within 60 degrees of hitting the shield head-on are blocked
+const SHIELD_BLOCK_COS: f32 = 0.5;
+
+// Blocks bullets that hit the side the shield is facing, the other sides can still be hit
+#[derive(Component)]
+pub struct DirectionalShield {
+    pub facing: Vec3,
+}
+
+impl DirectionalShield {
+    pub fn blocks(&self, bullet_direction: Vec3) -> bool {
+        // A bullet hitting the shielded side travels against the direction it is facing
+        bullet_direction.dot(self.facing) < -SHIELD_BLOCK_COS
+    }
+}
+
+const BURN_TICKS: u32 = 4;
+const BURN_TICK_INTERVAL_S: f32 = 0.5;
+const BURN_DAMAGE_PER_TICK: u32 = 1;
+
+// Damage over time from fire rounds, another hit restarts the burn instead of stacking it
+#[derive(Component)]
+pub struct Burning {
+    pub ticks_left: u32,
+    pub damage_per_tick: u32,
+    pub tick_timer: f32,
+}
+
+impl Default for Burning {
+    fn default() -> Self {
+        Self {
+            ticks_left: BURN_TICKS,
+            damage_per_tick: BURN_DAMAGE_PER_TICK,
+            tick_timer: BURN_TICK_INTERVAL_S,
+        }
+    }
+}
+
+impl Burning {
+    // Counts down to the next tick, returning true when the burn should deal its damage
+    pub fn tick(&mut self, delta_seconds: f32) -> bool {
+        self.tick_timer -= delta_seconds;
+        if self.tick_timer > 0.0 || self.ticks_left == 0 {
+            return false;
+        }
+        self.tick_timer += BURN_TICK_INTERVAL_S;
+        self.ticks_left -= 1;
+        true
+    }
+}
+
+// Bullets that reflect off the left/right walls of the playfield instead of flying off screen
+#[derive(Component)]
+pub struct Bouncing {
+    pub bounces_left: u32,
+}
+
+// The visible part of the playfield, centered on the origin
+#[derive(Resource)]
+pub struct PlayfieldBounds {
+    pub half_width: f32,
+}
+
+impl Default for PlayfieldBounds {
+    fn default() -> Self {
+        Self { half_width: 4.5 }
+    }
+}
+
+pub fn capped_delta_seconds(time: &Time) -> f32 {
+    time.delta_seconds().min(MAX_DELTA_S)
+}
+
+pub fn setup_bullet_trails(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    theme: Res<Theme>,
+) {
+    commands.insert_resource(BulletTrailAssets {
+        mesh: meshes.add(Mesh::from(shape::Capsule {
+            radius: 0.03,
+            depth: BULLET_TRAIL_LENGTH,
+            ..Default::default()
+        })),
+        player_material: materials.add(bullet_trail_material(&theme, true)),
+        enemy_material: materials.add(bullet_trail_material(&theme, false)),
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_bullet(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
+    theme: &Theme,
+    trail: &BulletTrailAssets,
     translation: Vec3,
+    direction: Vec3,
     is_player_bullet: bool,
-) {
+) -> Entity {
+    // A zero direction can't be rotated towards, so such a bullet keeps the default heading
+    let direction = direction.try_normalize().unwrap_or(if is_player_bullet {
+        PLAYER_BULLET_DIRECTION
+    } else {
+        ENEMY_BULLET_DIRECTION
+    });
     commands
         .spawn(SpatialBundle::default())
         .insert(Collider::cuboid(0.05, 0.05, 0.1))
         .insert(Sensor)
         .insert(Bullet {
             is_player_bullet,
-            up_direction: is_player_bullet,
+            direction,
             velocity: 7.5,
             damage: 1,
         })
         .insert(ActiveEvents::COLLISION_EVENTS)
-        .insert(TransformBundle::from(Transform::from_translation(
-            translation,
-        )))
+        .insert(TransformBundle::from(
+            // Align the collider and mesh, which are both modelled along z, with the direction
+            Transform::from_translation(translation)
+                .with_rotation(Quat::from_rotation_arc(Vec3::Z, direction)),
+        ))
         .with_children(|children| {
             children.spawn(PbrBundle {
                 mesh: meshes.add(Mesh::from(shape::Capsule {
@@ -69,10 +213,36 @@ pub fn spawn_bullet(
                 })),
                 transform: Transform::from_rotation(Quat::from_rotation_x(-90.0f32.to_radians())),
                 material: materials.add(StandardMaterial {
-                    emissive: Color::rgb_linear(35.0, 1.0, 2.0),
+                    emissive: theme.bullet(is_player_bullet),
                     ..Default::default()
                 }),
                 ..Default::default()
             });
-        });
+            // The bullet is aligned with its direction along local z, so the trail sits at -z
+            children
+                .spawn(PbrBundle {
+                    mesh: trail.mesh.clone(),
+                    transform: Transform::from_translation(Vec3::new(
+                        0.0,
+                        0.0,
+                        -BULLET_TRAIL_LENGTH * 0.5 - 0.05,
+                    ))
+                    .with_rotation(Quat::from_rotation_x(-90.0f32.to_radians())),
+                    material: trail.material(is_player_bullet).clone(),
+                    ..Default::default()
+                })
+                .insert(BulletTrail {});
+        })
+        .id()
+}
+
+pub fn bullet_trail_material(theme: &Theme, is_player_bullet: bool) -> StandardMaterial {
+    let color = theme.bullet(is_player_bullet);
+    StandardMaterial {
+        base_color: color.with_a(BULLET_TRAIL_ALPHA),
+        emissive: color * BULLET_TRAIL_GLOW,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..Default::default()
+    }
 }
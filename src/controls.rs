@@ -0,0 +1,212 @@
+use bevy::{
+    input::gamepad::{Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType},
+    prelude::{Axis, Input, KeyCode, Resource},
+};
+use serde::{Deserialize, Serialize};
+
+const BASE_MOVE_SPEED: f32 = 3.0;
+const MIN_SPEED_MULTIPLIER: f32 = 0.5;
+const MAX_SPEED_MULTIPLIER: f32 = 2.0;
+const SPEED_MULTIPLIER_STEP: f32 = 0.25;
+// How far the stick has to be pushed before the ship starts moving
+const STICK_DEADZONE: f32 = 0.5;
+
+#[derive(Resource)]
+pub struct KeyBindings {
+    pub move_left: KeyCode,
+    pub move_right: KeyCode,
+    pub fire: KeyCode,
+    // Held down to slow the ship and shrink its hitbox for precise dodging
+    pub focus: KeyCode,
+    pub quit_to_menu: KeyCode,
+    pub toggle_help: KeyCode,
+    pub toggle_color_blind: KeyCode,
+    // The second ship in co-op flies on the arrow keys
+    pub second_player: ShipKeys,
+}
+
+// The keys that fly a single ship
+#[derive(Clone, Copy)]
+pub struct ShipKeys {
+    pub move_left: KeyCode,
+    pub move_right: KeyCode,
+    pub fire: KeyCode,
+    pub focus: KeyCode,
+}
+
+// Which pilot a ship belongs to, the first one is also the only one outside of co-op
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlayerSlot {
+    #[default]
+    One,
+    Two,
+}
+
+impl PlayerSlot {
+    // Each pilot can also use a gamepad, in the order they were connected
+    pub fn gamepad_index(&self) -> usize {
+        match self {
+            PlayerSlot::One => 0,
+            PlayerSlot::Two => 1,
+        }
+    }
+}
+
+impl KeyBindings {
+    // Human readable (action, key) pairs, e.g. for the help overlay
+    pub fn describe(&self) -> Vec<(&'static str, KeyCode)> {
+        vec![
+            ("Move left", self.move_left),
+            ("Move right", self.move_right),
+            ("Fire", self.fire),
+            ("Focus (hold)", self.focus),
+            ("Quit to menu", self.quit_to_menu),
+            ("Toggle help", self.toggle_help),
+            ("Toggle color-blind mode", self.toggle_color_blind),
+            ("Player 2 move left", self.second_player.move_left),
+            ("Player 2 move right", self.second_player.move_right),
+            ("Player 2 fire", self.second_player.fire),
+            ("Player 2 focus (hold)", self.second_player.focus),
+        ]
+    }
+
+    // A key that is bound to more than one action, which global hotkeys have to leave alone
+    pub fn is_shared(&self, key: KeyCode) -> bool {
+        self.describe()
+            .iter()
+            .filter(|(_, bound)| *bound == key)
+            .count()
+            > 1
+    }
+
+    pub fn ship_keys(&self, slot: PlayerSlot) -> ShipKeys {
+        match slot {
+            PlayerSlot::One => ShipKeys {
+                move_left: self.move_left,
+                move_right: self.move_right,
+                fire: self.fire,
+                focus: self.focus,
+            },
+            PlayerSlot::Two => self.second_player,
+        }
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            move_left: KeyCode::A,
+            move_right: KeyCode::D,
+            fire: KeyCode::Space,
+            focus: KeyCode::ShiftLeft,
+            quit_to_menu: KeyCode::M,
+            toggle_help: KeyCode::H,
+            toggle_color_blind: KeyCode::C,
+            second_player: ShipKeys {
+                move_left: KeyCode::Left,
+                move_right: KeyCode::Right,
+                fire: KeyCode::Up,
+                focus: KeyCode::Down,
+            },
+        }
+    }
+}
+
+// What a ship's pilot is pressing this frame, on the keyboard or their gamepad
+pub struct ShipInput {
+    pub left: bool,
+    pub right: bool,
+    pub fire: bool,
+    pub fire_just_pressed: bool,
+    pub focus: bool,
+}
+
+impl ShipInput {
+    pub fn read(
+        keys: &ShipKeys,
+        input: &Input<KeyCode>,
+        gamepad: Option<Gamepad>,
+        buttons: &Input<GamepadButton>,
+        axes: &Axis<GamepadAxis>,
+    ) -> Self {
+        let button = |button_type| {
+            gamepad.is_some_and(|gamepad| buttons.pressed(GamepadButton::new(gamepad, button_type)))
+        };
+        let stick_x = gamepad
+            .and_then(|gamepad| axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX)))
+            .unwrap_or(0.0);
+        let fire_just_pressed = input.just_pressed(keys.fire)
+            || gamepad.is_some_and(|gamepad| {
+                buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South))
+            });
+
+        Self {
+            left: input.pressed(keys.move_left)
+                || button(GamepadButtonType::DPadLeft)
+                || stick_x < -STICK_DEADZONE,
+            right: input.pressed(keys.move_right)
+                || button(GamepadButtonType::DPadRight)
+                || stick_x > STICK_DEADZONE,
+            fire: input.pressed(keys.fire) || button(GamepadButtonType::South),
+            fire_just_pressed,
+            focus: input.pressed(keys.focus) || button(GamepadButtonType::RightTrigger),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlSettings {
+    // Scales the player's movement speed, between 0.5x and 2x
+    pub speed_multiplier: f32,
+    pub invert_horizontal: bool,
+}
+
+impl ControlSettings {
+    // Horizontal speed of the player, negative when the controls are inverted
+    pub fn move_speed(&self) -> f32 {
+        let direction = if self.invert_horizontal { -1.0 } else { 1.0 };
+        BASE_MOVE_SPEED * self.speed_multiplier * direction
+    }
+
+    // Steps through the allowed multipliers, wrapping back around to the slowest one
+    pub fn next_speed_multiplier(&self) -> f32 {
+        let next = self.speed_multiplier + SPEED_MULTIPLIER_STEP;
+        if next > MAX_SPEED_MULTIPLIER + f32::EPSILON {
+            MIN_SPEED_MULTIPLIER
+        } else {
+            next
+        }
+    }
+}
+
+impl Default for ControlSettings {
+    fn default() -> Self {
+        Self {
+            speed_multiplier: 1.0,
+            invert_horizontal: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_bindings_dont_share_keys() {
+        let bindings = KeyBindings::default();
+        for (_, key) in bindings.describe() {
+            assert!(!bindings.is_shared(key), "{:?} is bound twice", key);
+        }
+    }
+
+    #[test]
+    fn rebinding_onto_the_color_blind_key_shares_it() {
+        let bindings = KeyBindings {
+            fire: KeyCode::C,
+            ..Default::default()
+        };
+        assert!(bindings.is_shared(bindings.toggle_color_blind));
+    }
+}
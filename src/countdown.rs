@@ -0,0 +1,94 @@
+use bevy::{
+    prelude::{
+        default, AssetServer, BuildChildren, Commands, Component, DespawnRecursiveExt, Entity,
+        NodeBundle, Query, Res, ResMut, Resource, TextBundle, With,
+    },
+    text::{Text, TextStyle},
+    time::Time,
+    ui::{AlignItems, JustifyContent, PositionType, Style, Val},
+};
+
+use crate::theme::{Theme, ThemedUi};
+
+pub const START_COUNTDOWN_S: f32 = 3.0;
+
+// Present while the game is counting down, enemies hold their fire until it is removed
+#[derive(Resource)]
+pub struct StartCountdown {
+    pub timer: f32,
+}
+
+#[derive(Component)]
+pub struct StartCountdownUi {}
+
+#[derive(Component)]
+pub struct StartCountdownText {}
+
+pub fn update_start_countdown(
+    mut commands: Commands,
+    countdown: Option<ResMut<StartCountdown>>,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+    time: Res<Time>,
+    roots: Query<Entity, With<StartCountdownUi>>,
+    mut texts: Query<&mut Text, With<StartCountdownText>>,
+) {
+    let Some(mut countdown) = countdown else {
+        return;
+    };
+
+    countdown.timer -= time.delta_seconds();
+    if countdown.timer <= 0.0 {
+        commands.remove_resource::<StartCountdown>();
+        for root in roots.iter() {
+            commands.entity(root).despawn_recursive();
+        }
+        return;
+    }
+
+    let label = format!("{}", countdown.timer.ceil() as u32);
+    if roots.is_empty() {
+        let text_style = TextStyle {
+            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+            font_size: 120.0,
+            color: theme.text,
+        };
+
+        commands
+            .spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|parent| {
+                parent
+                    .spawn(TextBundle::from_section(label, text_style))
+                    .insert(StartCountdownText {})
+                    .insert(ThemedUi {});
+            })
+            .insert(StartCountdownUi {});
+        return;
+    }
+
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
+
+pub fn destroy_start_countdown(
+    mut commands: Commands,
+    roots: Query<Entity, With<StartCountdownUi>>,
+) {
+    commands.remove_resource::<StartCountdown>();
+    for root in roots.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+}
@@ -0,0 +1,83 @@
+use bevy::{
+    prelude::{
+        default, Color, Commands, Component, DespawnRecursiveExt, Entity, EventReader, NodeBundle,
+        Query, Res, ResMut, Resource, With,
+    },
+    time::Time,
+    ui::{BackgroundColor, PositionType, Style, Val},
+};
+
+use crate::combat::PlayerHitEvent;
+
+const DAMAGE_FLASH_DURATION_S: f32 = 0.2;
+const DAMAGE_FLASH_MAX_ALPHA: f32 = 0.5;
+
+// A full-screen flash, red when the player is hit but other events can flash other colors
+#[derive(Resource)]
+pub struct DamageFlash {
+    pub timer: f32,
+    pub color: Color,
+}
+
+impl DamageFlash {
+    pub fn trigger(&mut self, color: Color) {
+        self.timer = DAMAGE_FLASH_DURATION_S;
+        self.color = color;
+    }
+}
+
+impl Default for DamageFlash {
+    fn default() -> Self {
+        Self {
+            timer: 0.0,
+            color: Color::RED,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct DamageFlashOverlay {}
+
+pub fn setup_damage_flash(mut commands: Commands, mut flash: ResMut<DamageFlash>) {
+    flash.timer = 0.0;
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            background_color: Color::rgba(1.0, 0.0, 0.0, 0.0).into(),
+            ..default()
+        })
+        .insert(DamageFlashOverlay {});
+}
+
+pub fn destroy_damage_flash(
+    mut commands: Commands,
+    overlays: Query<Entity, With<DamageFlashOverlay>>,
+) {
+    for overlay in overlays.iter() {
+        commands.entity(overlay).despawn_recursive();
+    }
+}
+
+pub fn update_damage_flash(
+    mut flash: ResMut<DamageFlash>,
+    time: Res<Time>,
+    mut er: EventReader<PlayerHitEvent>,
+    mut overlays: Query<&mut BackgroundColor, With<DamageFlashOverlay>>,
+) {
+    // Restart the flash on every hit rather than stacking, so repeated hits don't linger
+    if er.iter().count() > 0 {
+        flash.trigger(Color::RED);
+    } else {
+        flash.timer = f32::max(flash.timer - time.delta_seconds(), 0.0);
+    }
+
+    let alpha = DAMAGE_FLASH_MAX_ALPHA * flash.timer / DAMAGE_FLASH_DURATION_S;
+    for mut color in overlays.iter_mut() {
+        color.0 = flash.color.with_a(alpha);
+    }
+}
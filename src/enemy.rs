@@ -1,6 +1,9 @@
 use bevy::prelude::Component;
 
+use crate::plugins::enemy_wave_plugin::EnemyType;
+
 #[derive(Component)]
 pub struct Enemy {
     pub shot_cooldown_timer: f32,
+    pub enemy_type: EnemyType,
 }
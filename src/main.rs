@@ -1,21 +1,31 @@
 mod camera;
 mod combat;
+mod controls;
+mod countdown;
+mod damage_flash;
 mod enemy;
+mod model_fallback;
 mod particles;
 mod plugins;
+mod rng;
+mod settings;
 mod state;
+mod theme;
 
-use std::ops::Add;
+use std::{collections::HashSet, ops::Add};
 
 use crate::plugins::enemy_wave_plugin::EnemyWavePlugin;
 use bevy::{
     core_pipeline::{bloom::BloomSettings, tonemapping::Tonemapping},
+    ecs::query::ReadOnlyWorldQuery,
     prelude::{
-        in_state, shape, AlphaMode, App, AssetServer, Assets, Camera, Camera3dBundle, Commands,
-        Component, DespawnRecursiveExt, Entity, EventWriter, Input, IntoSystemConfigs, KeyCode,
-        Mesh, NextState, OnEnter, OnExit, PbrBundle, PluginGroup, PointLight, PointLightBundle,
-        Quat, Query, Res, ResMut, Resource, StandardMaterial, Startup, Transform, Update, Vec2,
-        Vec3, With, Without,
+        apply_state_transition, in_state, shape, App, AssetServer, Assets, Axis, BuildChildren,
+        Camera, Camera3dBundle, Changed, Children, Color, Commands, Component, DespawnRecursiveExt,
+        Entity, EventWriter, Gamepad, GamepadAxis, GamepadButton, Gamepads, Input,
+        IntoSystemConfigs, KeyCode, Last, Mesh, NextState, OnEnter, OnExit, PbrBundle, PluginGroup,
+        PointLight, PointLightBundle, Quat, Query, Res, ResMut, Resource, SpatialBundle,
+        StandardMaterial, Startup, StateTransition, Transform, Update, Vec2, Vec3, Visibility,
+        With, Without,
     },
     render::{
         settings::{WgpuFeatures, WgpuSettings},
@@ -23,42 +33,152 @@ use bevy::{
     },
     scene::SceneBundle,
     time::Time,
+    ui::UiScale,
     window::Window,
     DefaultPlugins,
 };
 use bevy_hanabi::{CompiledParticleEffect, EffectAsset, EffectSpawner, HanabiPlugin};
 use bevy_rapier3d::{
     prelude::{
-        ActiveEvents, Collider, GravityScale, NoUserData, RapierContext, RapierPhysicsPlugin,
-        RigidBody, Sensor,
+        ActiveEvents, Collider, GravityScale, NoUserData, QueryFilter, RapierContext,
+        RapierPhysicsPlugin, RigidBody, Sensor,
     },
     render::RapierDebugRenderPlugin,
 };
-use camera::{on_hit_camera_shake, CameraShakeEvent, CameraState};
+use camera::{
+    apply_camera_view, on_hit_camera_shake, update_playfield_bounds, CameraShakeEvent, CameraState,
+};
 use combat::{
-    spawn_bullet, Bullet, Damageable, EntityDeath, LargeHitEffect, ParticleHitEffect,
-    SmallHitEffect,
+    capped_delta_seconds, setup_bullet_trails, spawn_bullet, Bouncing, Bullet, BulletTrailAssets,
+    Burning, Damageable, DirectionalShield, EntityDeath, LargeHitEffect, ParticleHitEffect,
+    PlayerHitEvent, PlayfieldBounds, SmallHitEffect, PLAYER_BULLET_DIRECTION,
+};
+use controls::{KeyBindings, PlayerSlot, ShipInput};
+use countdown::{
+    destroy_start_countdown, update_start_countdown, StartCountdown, START_COUNTDOWN_S,
 };
-use particles::create_effect;
+use damage_flash::{destroy_damage_flash, setup_damage_flash, update_damage_flash, DamageFlash};
+use model_fallback::replace_failed_models;
+use particles::{create_effect, destroy_particle_effects, spawn_shockwave, update_shockwaves};
 use plugins::{
+    achievements::{Achievements, AchievementsPlugin},
+    asteroids::{Asteroid, AsteroidPlugin},
+    boss::{BossAttackState, BossPlugin},
+    damage_numbers::{DamageNumber, DamageNumberPlugin, DAMAGE_NUMBER_DURATION_S},
     enemy_wave_plugin::EnemyAIState,
+    game_over::GameOverPlugin,
+    graze::GrazePlugin,
+    health_bars::HealthBarPlugin,
+    heat_bar::HeatBarPlugin,
+    help_overlay::HelpOverlayPlugin,
+    last_stand::LastStandPlugin,
+    leaderboard::{LeaderboardPlugin, RunEndedEvent},
     main_menu::MainMenuPlugin,
-    powerups::{Powerup, PowerupComponent, PowerupPlugin},
+    menu_navigation::MenuNavigationPlugin,
+    mines::MinePlugin,
+    music::MusicPlugin,
+    options_menu::OptionsMenuPlugin,
+    powerups::{ActivePowerups, Powerup, PowerupPlugin},
+    rewards::RewardPlugin,
+    support::SupportPlugin,
+    tutorial::TutorialPlugin,
 };
-use state::GameState;
+use rng::GameRng;
+use settings::{
+    apply_frame_limit, apply_settings, limit_frame_rate, load_settings, save_settings_on_change,
+    toggle_color_blind, Settings, WeaponModel,
+};
+use state::{guard_state_transitions, GameState};
+use theme::{apply_theme_to_bullets, apply_theme_to_ui, Theme};
+
+// Slightly smaller than the ship model so that grazing shots don't count as hits
+const PLAYER_COLLIDER_HALF_EXTENTS: Vec3 = Vec3::new(0.3, 0.1, 0.25);
+// While focused the ship moves slower and only a small core around its center can be hit
+const FOCUS_SPEED_MULTIPLIER: f32 = 0.4;
+const FOCUS_HITBOX_RADIUS: f32 = 0.08;
+// How long a tap on the fire key is remembered while the gun is still cooling down
+const FIRE_BUFFER_S: f32 = 0.1;
+// With the heat weapon model the gun fires faster, but every shot heats it up. Heat goes from 0
+// to 1, and an overheated gun has to cool down below the threshold before it fires again
+const HEAT_SHOT_INTERVAL_S: f32 = 0.15;
+const HEAT_PER_SHOT: f32 = 0.1;
+const HEAT_DISSIPATION_PER_S: f32 = 0.4;
+const OVERHEAT_RECOVERY_HEAT: f32 = 0.35;
+// How far the side bullets of double and triple shot angle away from straight ahead
+const SIDE_SHOT_SPREAD_DEG: f32 = 10.0;
+// How far the ship model rolls into sideways movement, and how quickly it eases in and out of it
+const PLAYER_MAX_BANK_DEG: f32 = 25.0;
+const PLAYER_BANK_RATE: f32 = 10.0;
+// Bullets further than this from the center of the playfield are despawned
+const BULLET_BOUNDS_RADIUS: f32 = 20.0;
+// How far a swept bullet is pushed into the collider it would have hit, so that the overlap is
+// picked up by the regular hit check
+const BULLET_SWEEP_OVERLAP: f32 = 0.05;
+
+// With explosive rounds, enemies within this radius of an enemy death take splash damage
+const CHAIN_REACTION_RADIUS: f32 = 1.1;
+const CHAIN_REACTION_DAMAGE: u32 = 1;
+// Limits how many times explosions can set off further explosions in a single frame
+const CHAIN_REACTION_MAX_STEPS: u32 = 3;
+
+// Score for an enemy finished off by a burn rather than a bullet
+const BURN_KILL_SCORE: u32 = 50;
+
+// Smallest the UI is scaled down to, however small the window gets
+const MIN_UI_SCALE: f64 = 0.25;
+
+// Long enough for the powerups to last through a whole practice boss fight
+const PRACTICE_BOSS_POWERUP_DURATION_S: f32 = 300.0;
 
 #[derive(Component, Default)]
 struct Player {
-    lives: u32,
     bullet_cooldown: f32,
     bullet_cooldown_timer: f32,
-    active_powerup: Option<Powerup>,
+    fire_buffer_timer: f32,
+    // Only used with the heat weapon model
+    heat: f32,
+    overheated: bool,
+    // -1.0 when moving left, 1.0 when moving right and 0.0 when idle
+    move_direction: f32,
+    focused: bool,
+    slot: PlayerSlot,
 }
 
+// The ship's model, a child of the player so that it can bank without turning the collider
+#[derive(Component)]
+struct PlayerModel {}
+
+// Marks the focused hitbox on the ship, only shown while focused
+#[derive(Component)]
+struct FocusHitboxDot {}
+
 #[derive(Resource, Default)]
 struct GameResources {
-    player: Option<Entity>,
+    // One ship, or two in co-op
+    players: Vec<Entity>,
+    // Set from the menu, and kept when continuing so the same pilots carry on
+    two_players: bool,
     score: u32,
+    // Set when the player spends score to continue, so the next game keeps the score
+    continuing: bool,
+    // Set from the menu to start straight at the boss with every powerup active. It lasts for the
+    // whole run, which keeps the run off the leaderboard and doesn't unlock achievements
+    practice_boss: bool,
+}
+
+impl GameResources {
+    // Enemies aim at whichever ship is closest to them
+    fn nearest_player<F: ReadOnlyWorldQuery>(
+        &self,
+        position: Vec3,
+        transforms: &Query<&Transform, F>,
+    ) -> Option<Vec3> {
+        self.players
+            .iter()
+            .filter_map(|player| transforms.get(*player).ok())
+            .map(|transform| transform.translation)
+            .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position)))
+    }
 }
 
 #[derive(Resource)]
@@ -66,9 +186,6 @@ struct ResolutionSettings {
     standard: Vec2,
 }
 
-#[derive(Component)]
-struct Background;
-
 fn main() {
     let mut wgpu_settings = WgpuSettings::default();
     wgpu_settings
@@ -81,19 +198,55 @@ fn main() {
         .add_plugins(bevy_obj::ObjPlugin)
         .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
         .add_plugins(RapierDebugRenderPlugin::default())
-        .add_plugins((MainMenuPlugin, EnemyWavePlugin, PowerupPlugin))
+        .add_plugins((
+            MainMenuPlugin,
+            EnemyWavePlugin,
+            PowerupPlugin,
+            LeaderboardPlugin,
+            HelpOverlayPlugin,
+            GameOverPlugin,
+            BossPlugin,
+            MenuNavigationPlugin,
+            AchievementsPlugin,
+            HealthBarPlugin,
+            TutorialPlugin,
+            RewardPlugin,
+            LastStandPlugin,
+            MinePlugin,
+            GrazePlugin,
+        ))
+        .add_plugins((
+            HeatBarPlugin,
+            AsteroidPlugin,
+            SupportPlugin,
+            DamageNumberPlugin,
+            OptionsMenuPlugin,
+            MusicPlugin,
+        ))
         .add_state::<GameState>()
+        .add_systems(
+            StateTransition,
+            guard_state_transitions.before(apply_state_transition::<GameState>),
+        )
         .init_resource::<GameResources>()
+        .init_resource::<KeyBindings>()
+        .init_resource::<Theme>()
         .insert_resource(ResolutionSettings {
             standard: Vec2::new(600.0, 1000.0),
         })
         .insert_resource(EnemyAIState::default())
         .insert_resource(CameraState::default())
+        .init_resource::<DamageFlash>()
+        .init_resource::<PlayfieldBounds>()
+        .init_resource::<GameRng>()
         .add_event::<CameraShakeEvent>()
+        .add_event::<PlayerHitEvent>()
         .add_systems(
             Startup,
             (
+                load_settings,
                 set_resolution,
+                setup_bullet_trails.after(load_settings),
                 setup_cameras,
                 setup_particle_systems,
                 setup_background,
@@ -101,21 +254,52 @@ fn main() {
         )
         .add_systems(
             OnEnter(GameState::Game), // run if in game state
-            setup_game_state,
+            (setup_game_state, setup_damage_flash),
+        )
+        .add_systems(
+            OnExit(GameState::Game),
+            (
+                destroy_entities,
+                destroy_particle_effects,
+                destroy_damage_flash,
+                destroy_start_countdown,
+            ),
         )
-        .add_systems(OnExit(GameState::Game), destroy_entities)
         .add_systems(
             Update,
             (
                 player_controls,
+                bank_player_model.after(player_controls),
+                show_focus_hitbox.after(player_controls),
                 bullet_controls,
                 check_bullet_damage,
+                update_burning.after(check_bullet_damage),
                 create_explosion_particle_system,
+                update_shockwaves,
                 on_hit_camera_shake,
+                update_damage_flash,
+                update_start_countdown,
                 destroy_bullets,
+                quit_to_menu,
             )
                 .run_if(in_state(GameState::Game)),
         )
+        .add_systems(
+            Update,
+            (
+                update_playfield_bounds,
+                update_ui_scale,
+                replace_failed_models,
+                toggle_color_blind,
+                apply_settings,
+                apply_frame_limit,
+                save_settings_on_change,
+                apply_camera_view,
+                apply_theme_to_ui,
+                apply_theme_to_bullets,
+            ),
+        )
+        .add_systems(Last, limit_frame_rate)
         .run();
 }
 
@@ -125,6 +309,30 @@ fn set_resolution(mut windows: Query<&mut Window>, resolution: Res<ResolutionSet
     window.resolution.set(resolution.x, resolution.y);
 }
 
+// The UI is laid out in pixels for the standard resolution, so scale it with the window height
+// to keep text and buttons proportional on larger or smaller windows. A minimized window has no
+// height at all, which would shrink the UI down to nothing
+fn ui_scale_for_window(window: &Window, resolution: &ResolutionSettings) -> f64 {
+    f64::max(
+        (window.height() / resolution.standard.y) as f64,
+        MIN_UI_SCALE,
+    )
+}
+
+fn update_ui_scale(
+    windows: Query<&Window, Changed<Window>>,
+    resolution: Res<ResolutionSettings>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let scale = ui_scale_for_window(window, &resolution);
+    if (ui_scale.scale - scale).abs() > 0.01 {
+        ui_scale.scale = scale;
+    }
+}
+
 fn setup_cameras(mut commands: Commands, _: ResMut<GameResources>, camera_state: Res<CameraState>) {
     commands.spawn((
         Camera3dBundle {
@@ -133,8 +341,7 @@ fn setup_cameras(mut commands: Commands, _: ResMut<GameResources>, camera_state:
                 ..Default::default()
             },
             tonemapping: Tonemapping::TonyMcMapface,
-            transform: Transform::from_translation(camera_state.original_position)
-                .looking_at(Vec3::new(0.0, 0.0, 0.0), Vec3::Y),
+            transform: camera_state.rest_transform(),
             ..Default::default()
         },
         BloomSettings::default(),
@@ -144,43 +351,46 @@ fn setup_cameras(mut commands: Commands, _: ResMut<GameResources>, camera_state:
 fn setup_game_state(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
     mut game: ResMut<GameResources>,
 ) {
-    game.score = 0;
-    if let Some(player) = game.player {
+    if !game.continuing {
+        game.score = 0;
+    }
+    game.continuing = false;
+    let powerups = if game.practice_boss {
+        ActivePowerups::fully_equipped(PRACTICE_BOSS_POWERUP_DURATION_S)
+    } else {
+        ActivePowerups::default()
+    };
+    commands.insert_resource(StartCountdown {
+        timer: START_COUNTDOWN_S,
+    });
+    for player in game.players.drain(..) {
         if let Some(player) = commands.get_entity(player) {
             player.despawn_recursive();
         }
     }
 
-    game.player = Some(
-        commands
-            .spawn(SceneBundle {
-                transform: Transform {
-                    translation: Vec3::new(0.0, 0.0, 7.0),
-                    rotation: Quat::from_rotation_y(90.0_f32.to_radians()),
-                    ..Default::default()
-                },
-                scene: asset_server.load("Spaceship4/model.obj"),
-                ..Default::default()
-            })
-            .insert(RigidBody::Dynamic)
-            .insert(Sensor {})
-            .insert(GravityScale(0.0))
-            .insert(Collider::cylinder(0.25, 0.3))
-            .insert(ActiveEvents::COLLISION_EVENTS)
-            .insert(Player {
-                lives: 3,
-                bullet_cooldown: 0.0,
-                bullet_cooldown_timer: 0.25,
-                active_powerup: None,
-            })
-            .insert(Damageable {
-                health: 5,
-                is_player: true,
-            })
-            .id(),
-    );
+    // In co-op the ships start side by side, each with their own health and powerups
+    let slots: &[(PlayerSlot, f32)] = if game.two_players {
+        &[(PlayerSlot::One, -1.5), (PlayerSlot::Two, 1.5)]
+    } else {
+        &[(PlayerSlot::One, 0.0)]
+    };
+    for (slot, x) in slots {
+        let player = spawn_player(
+            &mut commands,
+            &asset_server,
+            &mut meshes,
+            &mut materials,
+            *slot,
+            Vec3::new(*x, 0.0, 7.0),
+            powerups.clone(),
+        );
+        game.players.push(player);
+    }
 
     commands.spawn(PointLightBundle {
         point_light: PointLight {
@@ -195,6 +405,74 @@ fn setup_game_state(
     });
 }
 
+fn spawn_player(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    slot: PlayerSlot,
+    translation: Vec3,
+    powerups: ActivePowerups,
+) -> Entity {
+    commands
+        .spawn(SpatialBundle {
+            transform: Transform {
+                translation,
+                rotation: Quat::from_rotation_y(90.0_f32.to_radians()),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(RigidBody::Dynamic)
+        .insert(Sensor {})
+        .insert(GravityScale(0.0))
+        .insert(player_collider(false))
+        .insert(ActiveEvents::COLLISION_EVENTS)
+        .insert(Player {
+            bullet_cooldown: 0.0,
+            bullet_cooldown_timer: 0.25,
+            fire_buffer_timer: 0.0,
+            heat: 0.0,
+            overheated: false,
+            move_direction: 0.0,
+            focused: false,
+            slot,
+        })
+        .insert(powerups)
+        .insert(Damageable {
+            health: 5,
+            max_health: 5,
+            is_player: true,
+        })
+        .with_children(|children| {
+            children
+                .spawn(SceneBundle {
+                    scene: asset_server.load("Spaceship4/model.obj"),
+                    ..Default::default()
+                })
+                .insert(PlayerModel {});
+            children
+                .spawn(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::UVSphere {
+                        radius: FOCUS_HITBOX_RADIUS,
+                        ..Default::default()
+                    })),
+                    material: materials.add(StandardMaterial {
+                        base_color: Color::WHITE,
+                        emissive: Color::rgb_linear(4.0, 4.0, 4.0),
+                        unlit: true,
+                        ..Default::default()
+                    }),
+                    // Just above the model so that it isn't hidden inside the ship
+                    transform: Transform::from_xyz(0.0, 0.2, 0.0),
+                    visibility: Visibility::Hidden,
+                    ..Default::default()
+                })
+                .insert(FocusHitboxDot {});
+        })
+        .id()
+}
+
 fn destroy_entities(mut commands: Commands, query: Query<Entity, With<Bullet>>) {
     for bullet in query.iter() {
         commands.entity(bullet).despawn_recursive();
@@ -242,157 +520,543 @@ fn setup_background(
     });
 }
 
+#[allow(clippy::too_many_arguments)]
 fn player_controls(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut next_state: ResMut<NextState<GameState>>,
-    input: Res<Input<KeyCode>>,
-    game: ResMut<GameResources>,
-    mut player_query: Query<(&mut Transform, &mut Player, Option<&PowerupComponent>)>,
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    bindings: Res<KeyBindings>,
+    settings: Res<Settings>,
+    theme: Res<Theme>,
+    trail: Res<BulletTrailAssets>,
+    mut player_query: Query<(Entity, &mut Transform, &mut Player, &ActivePowerups)>,
     time: Res<Time>,
 ) {
-    if game.player.is_none() {
+    // The run is over once every ship has been destroyed
+    if player_query.is_empty() {
+        next_state.set(GameState::GameOver);
         return;
     }
-    let player_entity = game.player.unwrap();
 
-    let query = player_query.get_mut(player_entity);
-    if query.is_err() {
-        next_state.set(GameState::Menu);
-        return;
-    }
+    // Sorted so that each pilot keeps the same gamepad
+    let mut connected: Vec<Gamepad> = gamepads.iter().collect();
+    connected.sort_by_key(|gamepad| gamepad.id);
 
-    let mut player = query.unwrap();
-    let mut translation = player.0.translation;
+    let delta_time = capped_delta_seconds(&time);
+    for (player_entity, mut transform, mut player, powerups) in player_query.iter_mut() {
+        let gamepad = connected.get(player.slot.gamepad_index()).copied();
+        let input = ShipInput::read(
+            &bindings.ship_keys(player.slot),
+            &keys,
+            gamepad,
+            &gamepad_buttons,
+            &gamepad_axes,
+        );
+        let mut translation = transform.translation;
 
-    let move_speed = 3.0;
-    // Move left and right with A/D
-    if input.pressed(KeyCode::A) {
-        translation.x -= move_speed * time.delta_seconds();
-        *player.0 = Transform {
-            translation,
-            rotation: player.0.rotation,
-            ..Default::default()
+        // Focusing slows the ship down and swaps in the smaller hitbox
+        let focused = input.focus;
+        if focused != player.focused {
+            player.focused = focused;
+            commands
+                .entity(player_entity)
+                .insert(player_collider(focused));
         }
-    }
-    if input.pressed(KeyCode::D) {
-        translation.x += move_speed * time.delta_seconds();
-        *player.0 = Transform {
-            translation,
-            rotation: player.0.rotation,
-            ..Default::default()
+
+        // Inverted controls give a negative speed, which swaps the directions below
+        let mut move_speed = settings.controls.move_speed();
+        if focused {
+            move_speed *= FOCUS_SPEED_MULTIPLIER;
         }
-    }
+        let mut move_direction = 0.0;
+        // Move left and right, A/D by default and the arrow keys for the second ship
+        if input.left {
+            translation.x -= move_speed * delta_time;
+            move_direction -= 1.0;
+            transform.translation = translation;
+        }
+        if input.right {
+            translation.x += move_speed * delta_time;
+            move_direction += 1.0;
+            transform.translation = translation;
+        }
+        // Inverted controls move the ship the other way, so bank along with the actual movement
+        player.move_direction = move_direction * move_speed.signum();
 
-    let can_shoot = if player.1.bullet_cooldown <= 0.0 {
-        true
-    } else {
-        player.1.bullet_cooldown -= time.delta_seconds();
-        false
-    };
+        let mut can_shoot = if player.bullet_cooldown <= 0.0 {
+            true
+        } else {
+            player.bullet_cooldown -= time.delta_seconds();
+            false
+        };
+
+        // The gun always cools down, but once overheated it stays locked until it is cool enough
+        let heat_model = settings.weapon_model == WeaponModel::Heat;
+        let cooled = HEAT_DISSIPATION_PER_S * time.delta_seconds();
+        player.heat = f32::max(player.heat - cooled, 0.0);
+        if player.overheated && player.heat <= OVERHEAT_RECOVERY_HEAT {
+            player.overheated = false;
+        }
+        if player.overheated {
+            can_shoot = false;
+        }
 
-    if can_shoot && input.pressed(KeyCode::Space) {
-        player.1.bullet_cooldown = player.1.bullet_cooldown_timer;
-        let mut spawn_positions = Vec::new();
-        spawn_positions.push(Vec3::new(0.0, 0.0, -0.5));
-        if let Some(powerup) = player.2 {
-            match powerup.powerup {
-                Powerup::DoubleShot => {
-                    spawn_positions.push(Vec3::new(-0.2, 0.0, 0.0));
+        if input.fire_just_pressed {
+            player.fire_buffer_timer = FIRE_BUFFER_S;
+        } else {
+            player.fire_buffer_timer -= time.delta_seconds();
+        }
+        let wants_to_shoot = input.fire || player.fire_buffer_timer > 0.0;
+
+        if can_shoot && wants_to_shoot {
+            player.fire_buffer_timer = 0.0;
+            if heat_model {
+                player.bullet_cooldown = HEAT_SHOT_INTERVAL_S;
+                player.heat += HEAT_PER_SHOT;
+                if player.heat >= 1.0 {
+                    player.heat = 1.0;
+                    player.overheated = true;
                 }
-                Powerup::TripleShot => {
-                    spawn_positions.push(Vec3::new(-0.2, 0.0, 0.0));
-                    spawn_positions.push(Vec3::new(0.2, 0.0, 0.0));
+            } else {
+                player.bullet_cooldown = player.bullet_cooldown_timer;
+            }
+            // Side shots fan outwards, rotating around y turns the -z direction towards -x
+            let left_shot = (
+                Vec3::new(-0.2, 0.0, 0.0),
+                Quat::from_rotation_y(SIDE_SHOT_SPREAD_DEG.to_radians()) * PLAYER_BULLET_DIRECTION,
+            );
+            let right_shot = (
+                Vec3::new(0.2, 0.0, 0.0),
+                Quat::from_rotation_y(-SIDE_SHOT_SPREAD_DEG.to_radians()) * PLAYER_BULLET_DIRECTION,
+            );
+            let mut shots = vec![(Vec3::new(0.0, 0.0, -0.5), PLAYER_BULLET_DIRECTION)];
+            for powerup in powerups.powerups.iter() {
+                match powerup.powerup {
+                    Powerup::DoubleShot => {
+                        shots.push(left_shot);
+                    }
+                    Powerup::TripleShot => {
+                        shots.push(left_shot);
+                        shots.push(right_shot);
+                    }
+                    Powerup::ExplosiveRounds | Powerup::Magnet | Powerup::FireRounds => {}
                 }
             }
+            for (pos, direction) in shots {
+                spawn_bullet(
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                    &theme,
+                    &trail,
+                    translation.add(pos),
+                    direction,
+                    true,
+                );
+            }
         }
-        for pos in spawn_positions {
-            spawn_bullet(
-                &mut commands,
-                &mut meshes,
-                &mut materials,
-                translation.add(pos),
-                true,
-            );
+    }
+}
+
+fn player_collider(focused: bool) -> Collider {
+    if focused {
+        Collider::ball(FOCUS_HITBOX_RADIUS)
+    } else {
+        Collider::cuboid(
+            PLAYER_COLLIDER_HALF_EXTENTS.x,
+            PLAYER_COLLIDER_HALF_EXTENTS.y,
+            PLAYER_COLLIDER_HALF_EXTENTS.z,
+        )
+    }
+}
+
+fn show_focus_hitbox(
+    players: Query<(&Player, &Children)>,
+    mut dots: Query<&mut Visibility, With<FocusHitboxDot>>,
+) {
+    for (player, children) in players.iter() {
+        for child in children.iter() {
+            if let Ok(mut visibility) = dots.get_mut(*child) {
+                *visibility = if player.focused {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+            }
         }
     }
 }
 
+// Rolls the ship model into the direction it is moving in and back upright when idle
+fn bank_player_model(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    players: Query<(&Player, &Children)>,
+    mut models: Query<&mut Transform, With<PlayerModel>>,
+) {
+    let blend = 1.0 - (-PLAYER_BANK_RATE * capped_delta_seconds(&time)).exp();
+    for (player, children) in players.iter() {
+        // The player's rotation turns the model's x axis to point forwards, so rolling around
+        // it keeps the base facing. A positive roll dips the right wing. Reduced motion keeps the
+        // ship level
+        let bank = if settings.reduced_motion {
+            0.0
+        } else {
+            player.move_direction * PLAYER_MAX_BANK_DEG.to_radians()
+        };
+        let target = Quat::from_rotation_x(bank);
+        for child in children.iter() {
+            if let Ok(mut transform) = models.get_mut(*child) {
+                transform.rotation = transform.rotation.slerp(target, blend);
+            }
+        }
+    }
+}
+
+// Bails out of the current run, Escape is left free for pausing
+fn quit_to_menu(
+    input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    game: Res<GameResources>,
+    ai_state: Res<EnemyAIState>,
+    mut run_ended: EventWriter<RunEndedEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if input.just_pressed(bindings.quit_to_menu) {
+        run_ended.send(RunEndedEvent {
+            score: game.score,
+            wave: ai_state.current_wave,
+        });
+        next_state.set(GameState::Menu);
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn check_bullet_damage(
     mut commands: Commands,
     rapier_context: Res<RapierContext>,
     mut ev: EventWriter<CameraShakeEvent>,
+    mut player_hit: EventWriter<PlayerHitEvent>,
+    mut achievements: ResMut<Achievements>,
     mut damageables: Query<
-        (Entity, &mut Damageable, &Transform),
+        (
+            Entity,
+            &mut Damageable,
+            &Transform,
+            Option<&BossAttackState>,
+        ),
         (With<Collider>, With<Damageable>),
     >,
-    bullets: Query<(Entity, &Bullet), With<Collider>>,
+    bullets: Query<(Entity, &Bullet, &Transform), With<Collider>>,
+    shields: Query<&DirectionalShield>,
+    player_powerups: Query<&ActivePowerups, With<Player>>,
+    settings: Res<Settings>,
 ) {
     // TODO: Consider doing the deletion, spawning particle effects, etc. in another system
 
-    for (damageable_entity, mut damageable, position) in damageables.iter_mut() {
-        for (bullet_entity, bullet) in &bullets {
-            // Check what the bullets are hitting
-            // Checks for intersections between Damageable things and the bullets
-            if rapier_context.intersection_pair(damageable_entity, bullet_entity) == Some(true) {
-                damageable.health = damageable.health.checked_sub(bullet.damage).unwrap_or(0);
-                let mut intensity = 0.5;
-                let mut entity_died = false;
+    // Check what the bullets are hitting first, then apply the damage further down
+    let targets: Vec<(Entity, bool)> = damageables
+        .iter()
+        .map(|(entity, damageable, _, _)| (entity, damageable.is_player))
+        .collect();
+    let bullet_hits = find_bullet_targets(
+        bullets
+            .iter()
+            .map(|(entity, bullet, _)| (entity, bullet.is_player_bullet)),
+        &targets,
+        |bullet, target| rapier_context.intersection_pair(target, bullet) == Some(true),
+    );
+    let mut hits = Vec::new();
+    for (bullet_entity, damageable_entity) in bullet_hits {
+        let Ok((_, bullet, bullet_transform)) = bullets.get(bullet_entity) else {
+            continue;
+        };
+        commands.entity(bullet_entity).despawn_recursive();
+        let blocked = shields
+            .get(damageable_entity)
+            .is_ok_and(|shield| shield.blocks(bullet.direction));
+        if blocked {
+            // Absorbed by the shield, just a spark where it hit
+            commands.spawn(ParticleHitEffect {
+                position: bullet_transform.translation,
+                is_large: false,
+            });
+        } else {
+            hits.push((
+                damageable_entity,
+                bullet.damage,
+                bullet_transform.translation,
+            ));
+        }
+    }
+
+    let fire_rounds = player_powerups
+        .iter()
+        .any(|active| active.has(&Powerup::FireRounds));
 
-                // Prevent the player from damaging itself & enemies from damaging eachother
-                if damageable.is_player != bullet.is_player_bullet {
-                    commands.entity(bullet_entity).despawn_recursive();
-                    if damageable.health == 0 {
-                        commands.entity(damageable_entity).despawn_recursive();
-
-                        // Spawn a particle system as a death effect
-                        commands.spawn(EntityDeath {
-                            position: position.translation,
-                            is_player: damageable.is_player,
-                        });
-
-                        intensity = 1.0;
-                        entity_died = true;
-                    }
+    // Then apply the damage, so nothing is despawned twice when several bullets hit at once
+    let mut dead = HashSet::new();
+    let mut enemy_deaths = Vec::new();
+    for (damageable_entity, damage, hit_position) in hits {
+        if dead.contains(&damageable_entity) {
+            continue;
+        }
+        let Ok((_, mut damageable, position, boss)) = damageables.get_mut(damageable_entity) else {
+            continue;
+        };
 
-                    ev.send(CameraShakeEvent { intensity });
-                    commands.spawn(ParticleHitEffect {
-                        position: position.translation,
-                        is_large: entity_died,
-                    });
-                }
+        damageable.health = damageable.health.saturating_sub(damage);
+        if settings.damage_numbers {
+            commands.spawn(DamageNumber {
+                value: damage,
+                timer: DAMAGE_NUMBER_DURATION_S,
+                position: hit_position,
+            });
+        }
+        let mut intensity = 0.5;
+        let mut entity_died = false;
+
+        if damageable.is_player {
+            player_hit.send(PlayerHitEvent {});
+            achievements.record_player_hit();
+        }
+        if damageable.health == 0 {
+            dead.insert(damageable_entity);
+            commands.entity(damageable_entity).despawn_recursive();
+            if !damageable.is_player {
+                achievements.record_kill(boss.is_some());
+                enemy_deaths.push(position.translation);
             }
+
+            // Spawn a particle system as a death effect
+            commands.spawn(EntityDeath {
+                position: position.translation,
+                is_player: damageable.is_player,
+            });
+
+            intensity = 1.0;
+            entity_died = true;
+        } else if fire_rounds && !damageable.is_player {
+            // Replacing the component refreshes the burn rather than stacking it
+            commands
+                .entity(damageable_entity)
+                .insert(Burning::default());
         }
+
+        ev.send(CameraShakeEvent { intensity });
+        commands.spawn(ParticleHitEffect {
+            position: position.translation,
+            is_large: entity_died,
+        });
+    }
+
+    let explosive_rounds = player_powerups
+        .iter()
+        .any(|active| active.has(&Powerup::ExplosiveRounds));
+    if !explosive_rounds {
+        return;
+    }
+
+    // Chain reaction, every step damages the enemies around the deaths of the previous step
+    for _ in 0..CHAIN_REACTION_MAX_STEPS {
+        if enemy_deaths.is_empty() {
+            break;
+        }
+        ev.send(CameraShakeEvent { intensity: 1.0 });
+
+        let mut chained_deaths = Vec::new();
+        for (damageable_entity, mut damageable, position, boss) in damageables.iter_mut() {
+            // Enemies that already died this frame are still around until the commands are applied
+            if damageable.is_player || damageable.health == 0 {
+                continue;
+            }
+            let in_range = enemy_deaths
+                .iter()
+                .any(|death| death.distance(position.translation) <= CHAIN_REACTION_RADIUS);
+            if !in_range {
+                continue;
+            }
+
+            damageable.health = damageable.health.saturating_sub(CHAIN_REACTION_DAMAGE);
+            let entity_died = damageable.health == 0;
+            if entity_died {
+                commands.entity(damageable_entity).despawn_recursive();
+                achievements.record_kill(boss.is_some());
+                commands.spawn(EntityDeath {
+                    position: position.translation,
+                    is_player: false,
+                });
+                chained_deaths.push(position.translation);
+            }
+            commands.spawn(ParticleHitEffect {
+                position: position.translation,
+                is_large: entity_died,
+            });
+        }
+        enemy_deaths = chained_deaths;
     }
 }
 
+// Pairs every bullet with the damageable it hit, given as (entity, is player) for both. Each
+// bullet is consumed by at most one damageable even if it overlaps several of them, so it is
+// only despawned and counted once
+fn find_bullet_targets(
+    bullets: impl Iterator<Item = (Entity, bool)>,
+    damageables: &[(Entity, bool)],
+    overlapping: impl Fn(Entity, Entity) -> bool,
+) -> Vec<(Entity, Entity)> {
+    bullets
+        .filter_map(|(bullet, is_player_bullet)| {
+            damageables
+                .iter()
+                // Prevent the player from damaging itself & enemies from damaging eachother
+                .filter(|(_, is_player)| *is_player != is_player_bullet)
+                .find(|(damageable, _)| overlapping(bullet, *damageable))
+                .map(|(damageable, _)| (bullet, *damageable))
+        })
+        .collect()
+}
+
+fn update_burning(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut game: ResMut<GameResources>,
+    mut achievements: ResMut<Achievements>,
+    mut burning: Query<(
+        Entity,
+        &mut Burning,
+        &mut Damageable,
+        &Transform,
+        Option<&BossAttackState>,
+    )>,
+) {
+    for (entity, mut burn, mut damageable, transform, boss) in burning.iter_mut() {
+        // Enemies that were killed by a bullet this frame are still around until the commands
+        // are applied
+        if damageable.health == 0 || !burn.tick(time.delta_seconds()) {
+            continue;
+        }
+
+        damageable.health = damageable.health.saturating_sub(burn.damage_per_tick);
+        let entity_died = damageable.health == 0;
+        if entity_died {
+            // Burning kills count just like any other kill, and are paid out to the player
+            commands.entity(entity).despawn_recursive();
+            game.score += BURN_KILL_SCORE;
+            achievements.record_kill(boss.is_some());
+            commands.spawn(EntityDeath {
+                position: transform.translation,
+                is_player: false,
+            });
+        } else if burn.ticks_left == 0 {
+            commands.entity(entity).remove::<Burning>();
+        }
+        // Small embers on every tick
+        commands.spawn(ParticleHitEffect {
+            position: transform.translation,
+            is_large: entity_died,
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn bullet_controls(
-    _: ResMut<GameResources>,
-    mut bullets: Query<(&mut Transform, &Bullet), With<Collider>>,
+    mut commands: Commands,
+    bounds: Res<PlayfieldBounds>,
+    rapier_context: Res<RapierContext>,
+    mut bullets: Query<(
+        Entity,
+        &mut Transform,
+        &mut Bullet,
+        &Collider,
+        Option<&mut Bouncing>,
+    )>,
+    targets: Query<&Damageable, With<Collider>>,
+    asteroids: Query<With<Asteroid>>,
     time: Res<Time>,
+    settings: Res<Settings>,
 ) {
-    let delta_time = time.delta_seconds();
-    for (mut transform, bullet) in bullets.iter_mut() {
-        let direction = if bullet.up_direction { -1.0 } else { 1.0 };
-        transform.translation.z += direction * bullet.velocity * delta_time;
+    let delta_time = capped_delta_seconds(&time);
+    for (bullet_entity, mut transform, mut bullet, collider, bouncing) in bullets.iter_mut() {
+        // Only the enemy bullets are affected by the game speed
+        let speed = if bullet.is_player_bullet {
+            1.0
+        } else {
+            settings.game_speed.0
+        };
+        let movement = bullet.direction * bullet.velocity * speed * delta_time;
+
+        // Sweep the bullet along its path so a large step can't carry it through a collider
+        // between two hit checks, and stop it just inside the first one it would hit instead
+        let is_player_bullet = bullet.is_player_bullet;
+        let is_target = |entity: Entity| {
+            asteroids.contains(entity)
+                || targets
+                    .get(entity)
+                    .is_ok_and(|target| target.is_player != is_player_bullet)
+        };
+        let filter = QueryFilter::new()
+            .exclude_collider(bullet_entity)
+            .predicate(&is_target);
+        let hit = rapier_context.cast_shape(
+            transform.translation,
+            transform.rotation,
+            movement,
+            collider,
+            1.0,
+            filter,
+        );
+        transform.translation += match hit {
+            Some((_, toi)) => movement * toi.toi + bullet.direction * BULLET_SWEEP_OVERLAP,
+            None => movement,
+        };
+
+        // Bouncing bullets reflect off the side walls until they run out of bounces
+        let Some(mut bouncing) = bouncing else {
+            continue;
+        };
+        let x = transform.translation.x;
+        let hit_wall = (x > bounds.half_width && bullet.direction.x > 0.0)
+            || (x < -bounds.half_width && bullet.direction.x < 0.0);
+        if !hit_wall {
+            continue;
+        }
+        if bouncing.bounces_left == 0 {
+            commands.entity(bullet_entity).despawn_recursive();
+            continue;
+        }
+
+        bouncing.bounces_left -= 1;
+        bullet.direction.x = -bullet.direction.x;
+        transform.translation.x = x.clamp(-bounds.half_width, bounds.half_width);
+        transform.rotation = Quat::from_rotation_arc(Vec3::Z, bullet.direction);
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn destroy_bullets(
     mut commands: Commands,
     bullets: Query<(Entity, &Transform), (With<Bullet>, With<Collider>)>,
 ) {
     for (bullet_entity, bullet_transform) in bullets.iter() {
         // Despawn due to out of bounds
-        if f32::abs(bullet_transform.translation.z) > 20. {
+        if bullet_transform.translation.length() > BULLET_BOUNDS_RADIUS {
             commands.entity(bullet_entity).despawn_recursive();
         }
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn create_explosion_particle_system(
     mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    settings: Res<Settings>,
     mut small_effect: Query<
         (
             &mut CompiledParticleEffect,
@@ -424,6 +1088,15 @@ fn create_explosion_particle_system(
         if particle_effect.is_large {
             large_transform.translation = particle_effect.position;
             large_spawner.reset();
+            // The expanding ring is left out with reduced motion, the burst itself still shows
+            if !settings.reduced_motion {
+                spawn_shockwave(
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                    particle_effect.position,
+                );
+            }
         } else {
             small_transform.translation = particle_effect.position;
             small_spawner.reset();
@@ -431,3 +1104,173 @@ fn create_explosion_particle_system(
         commands.entity(entity).despawn();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        asset::{AddAsset, AssetPlugin},
+        hierarchy::HierarchyPlugin,
+        prelude::{MinimalPlugins, TransformBundle},
+        scene::ScenePlugin,
+        time::TimeUpdateStrategy,
+        transform::TransformPlugin,
+    };
+
+    use super::*;
+    use crate::combat::MAX_DELTA_S;
+
+    // Just enough of the engine for rapier to find the overlaps between colliders
+    fn physics_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            HierarchyPlugin,
+            AssetPlugin::default(),
+            ScenePlugin,
+        ))
+        .add_asset::<Mesh>()
+        .add_plugins(RapierPhysicsPlugin::<NoUserData>::default());
+        app
+    }
+
+    fn spawn_enemy(app: &mut App, translation: Vec3) -> Entity {
+        spawn_enemy_with_collider(app, translation, Collider::cuboid(0.5, 0.5, 0.5))
+    }
+
+    fn spawn_enemy_with_collider(app: &mut App, translation: Vec3, collider: Collider) -> Entity {
+        app.world
+            .spawn((
+                TransformBundle::from(Transform::from_translation(translation)),
+                RigidBody::Dynamic,
+                GravityScale(0.0),
+                collider,
+                Damageable {
+                    health: 3,
+                    max_health: 3,
+                    is_player: false,
+                },
+            ))
+            .id()
+    }
+
+    fn spawn_player_bullet(app: &mut App, translation: Vec3) -> Entity {
+        app.world
+            .spawn((
+                TransformBundle::from(Transform::from_translation(translation)),
+                Collider::cuboid(0.05, 0.05, 0.1),
+                Sensor,
+                Bullet {
+                    is_player_bullet: true,
+                    direction: PLAYER_BULLET_DIRECTION,
+                    velocity: 7.5,
+                    damage: 1,
+                },
+            ))
+            .id()
+    }
+
+    #[test]
+    fn bullet_targets_skip_its_own_side_and_take_the_first_overlap() {
+        let bullets = [(Entity::from_raw(0), true), (Entity::from_raw(1), false)];
+        let player = (Entity::from_raw(2), true);
+        let enemies = [(Entity::from_raw(3), false), (Entity::from_raw(4), false)];
+        let damageables = [player, enemies[0], enemies[1]];
+
+        let hits = find_bullet_targets(bullets.into_iter(), &damageables, |_, _| true);
+        assert_eq!(
+            hits,
+            vec![(bullets[0].0, enemies[0].0), (bullets[1].0, player.0)]
+        );
+    }
+
+    #[test]
+    fn bullet_overlapping_two_enemies_damages_one() {
+        let mut app = physics_app();
+        app.init_resource::<Achievements>()
+            .init_resource::<Settings>()
+            .add_event::<CameraShakeEvent>()
+            .add_event::<PlayerHitEvent>()
+            .add_systems(Update, check_bullet_damage);
+        let enemies = [
+            spawn_enemy(&mut app, Vec3::new(-0.2, 0.0, 0.0)),
+            spawn_enemy(&mut app, Vec3::new(0.2, 0.0, 0.0)),
+        ];
+        let bullet = spawn_player_bullet(&mut app, Vec3::ZERO);
+
+        // The overlaps are found by the physics step at the end of the first frame
+        app.update();
+        app.update();
+
+        assert!(app.world.get_entity(bullet).is_none());
+        let damaged = enemies
+            .iter()
+            .filter(|enemy| app.world.get::<Damageable>(**enemy).unwrap().health < 3)
+            .count();
+        assert_eq!(damaged, 1);
+        let health: u32 = enemies
+            .iter()
+            .map(|enemy| app.world.get::<Damageable>(*enemy).unwrap().health)
+            .sum();
+        assert_eq!(health, 5);
+    }
+
+    #[test]
+    fn fast_bullet_hits_a_thin_collider_in_a_long_frame() {
+        let mut app = physics_app();
+        // Longer than the cap, so every frame takes the longest step there is
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            std::time::Duration::from_secs_f32(MAX_DELTA_S * 2.0),
+        ))
+        .init_resource::<Achievements>()
+        .init_resource::<Settings>()
+        .init_resource::<PlayfieldBounds>()
+        .add_event::<CameraShakeEvent>()
+        .add_event::<PlayerHitEvent>()
+        .add_systems(Update, (bullet_controls, check_bullet_damage).chain());
+        let wall = spawn_enemy_with_collider(
+            &mut app,
+            Vec3::new(0.0, 0.0, -1.0),
+            Collider::cuboid(0.5, 0.5, 0.01),
+        );
+        // Let the physics pick up the wall before the bullet starts moving
+        app.update();
+
+        // Moves 2 units in a single step, far past the 0.02 thick wall without the sweep
+        let bullet = spawn_player_bullet(&mut app, Vec3::ZERO);
+        app.world.get_mut::<Bullet>(bullet).unwrap().velocity = 2.0 / MAX_DELTA_S;
+        app.update();
+        app.update();
+
+        assert!(app.world.get_entity(bullet).is_none());
+        assert_eq!(app.world.get::<Damageable>(wall).unwrap().health, 2);
+    }
+
+    fn window_with_height(height: f32) -> Window {
+        let mut window = Window::default();
+        window.resolution.set(600.0, height);
+        window
+    }
+
+    #[test]
+    fn ui_scale_follows_the_window_height() {
+        let resolution = ResolutionSettings {
+            standard: Vec2::new(600.0, 1000.0),
+        };
+        for (height, scale) in [(1000.0, 1.0), (2000.0, 2.0), (500.0, 0.5)] {
+            let window = window_with_height(height);
+            assert!((ui_scale_for_window(&window, &resolution) - scale).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn ui_scale_stays_above_the_minimum_when_minimized() {
+        let resolution = ResolutionSettings {
+            standard: Vec2::new(600.0, 1000.0),
+        };
+        for height in [0.0, 100.0] {
+            let window = window_with_height(height);
+            assert_eq!(ui_scale_for_window(&window, &resolution), MIN_UI_SCALE);
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use bevy::{
+    asset::LoadState,
+    log::error,
+    prelude::{
+        shape, AssetServer, Assets, BuildChildren, Color, Commands, Component, Entity, Handle,
+        Mesh, PbrBundle, Query, Res, ResMut, StandardMaterial, Transform, Without,
+    },
+    scene::Scene,
+};
+
+const PLACEHOLDER_SIZE: f32 = 0.5;
+const PLACEHOLDER_COLOR: Color = Color::rgb(1.0, 0.0, 1.0);
+
+// Marks a model that failed to load and has already been given a placeholder
+#[derive(Component)]
+pub struct ModelPlaceholder {}
+
+// Ship models that fail to load would otherwise leave an invisible entity with a working
+// collider behind, so swap in a brightly colored cube instead.
+pub fn replace_failed_models(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    models: Query<(Entity, &Handle<Scene>, &Transform), Without<ModelPlaceholder>>,
+) {
+    for (entity, scene, transform) in models.iter() {
+        if asset_server.get_load_state(scene) != LoadState::Failed {
+            continue;
+        }
+
+        error!(
+            "Failed to load model {:?}, using a placeholder instead",
+            asset_server.get_handle_path(scene)
+        );
+
+        commands
+            .entity(entity)
+            .insert(ModelPlaceholder {})
+            .with_children(|children| {
+                children.spawn(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Cube {
+                        size: PLACEHOLDER_SIZE,
+                    })),
+                    material: materials.add(StandardMaterial {
+                        base_color: PLACEHOLDER_COLOR,
+                        unlit: true,
+                        ..Default::default()
+                    }),
+                    // Undo the model's scale so the placeholder has the same size for every ship
+                    transform: Transform::from_scale(transform.scale.recip()),
+                    ..Default::default()
+                });
+            });
+    }
+}
@@ -1,17 +1,29 @@
-use bevy::prelude::{Assets, Commands, Component, Name, ResMut, Vec2, Vec3, Vec4};
+use bevy::{
+    prelude::{
+        shape, AlphaMode, Assets, Color, Commands, Component, DespawnRecursiveExt, Entity, Handle,
+        Mesh, Name, Or, PbrBundle, Query, Res, ResMut, StandardMaterial, Transform, Vec2, Vec3,
+        Vec4, With,
+    },
+    time::Time,
+};
 use bevy_hanabi::{
     Attribute, ColorOverLifetimeModifier, EffectAsset, ExprWriter, Gradient, LinearDragModifier,
     ParticleEffectBundle, ScalarType, SetAttributeModifier, SetPositionSphereModifier,
     SetVelocitySphereModifier, ShapeDimension, SizeOverLifetimeModifier, Spawner,
 };
 
-use crate::combat::{LargeHitEffect, SmallHitEffect};
+use crate::combat::{EntityDeath, LargeHitEffect, ParticleHitEffect, SmallHitEffect};
 
-#[derive(Component)]
-pub struct SmallExplosion;
+const SHOCKWAVE_DURATION_S: f32 = 0.4;
+const SHOCKWAVE_MAX_RADIUS: f32 = 2.5;
 
+// An expanding ring that fades out, drawn on large deaths
 #[derive(Component)]
-pub struct LargeExplosion;
+pub struct Shockwave {
+    pub radius: f32,
+    pub max_radius: f32,
+    pub timer: f32,
+}
 
 pub fn create_effect(
     name: &str,
@@ -83,3 +95,123 @@ pub fn create_effect(
             .insert(SmallHitEffect {});
     }
 }
+
+pub fn spawn_shockwave(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    position: Vec3,
+) {
+    commands
+        .spawn(PbrBundle {
+            // A unit ring, the radius is applied through the scale
+            mesh: meshes.add(Mesh::from(shape::Torus {
+                radius: 1.0,
+                ring_radius: 0.05,
+                ..Default::default()
+            })),
+            material: materials.add(StandardMaterial {
+                base_color: Color::rgba(1.0, 0.8, 0.4, 1.0),
+                emissive: Color::rgb_linear(8.0, 4.0, 1.0),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..Default::default()
+            }),
+            transform: Transform::from_translation(position).with_scale(Vec3::ZERO),
+            ..Default::default()
+        })
+        .insert(Shockwave {
+            radius: 0.0,
+            max_radius: SHOCKWAVE_MAX_RADIUS,
+            timer: SHOCKWAVE_DURATION_S,
+        });
+}
+
+pub fn update_shockwaves(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut shockwaves: Query<(
+        Entity,
+        &mut Shockwave,
+        &mut Transform,
+        &Handle<StandardMaterial>,
+    )>,
+) {
+    for (entity, mut shockwave, mut transform, material) in shockwaves.iter_mut() {
+        shockwave.timer -= time.delta_seconds();
+        if shockwave.timer <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let progress = 1.0 - shockwave.timer / SHOCKWAVE_DURATION_S;
+        shockwave.radius = shockwave.max_radius * progress;
+        // Only grow the ring in the playfield plane so it doesn't get thicker as it expands
+        transform.scale = Vec3::new(shockwave.radius, 1.0, shockwave.radius);
+
+        if let Some(material) = materials.get_mut(material) {
+            let alpha = 1.0 - progress;
+            material.base_color.set_a(alpha);
+            material.emissive = Color::rgb_linear(8.0 * alpha, 4.0 * alpha, alpha);
+        }
+    }
+}
+
+// Shockwaves and queued hit and death effects only live during a game, while the pooled
+// hit/death spawners from `create_effect` are kept around for the next one
+#[allow(clippy::type_complexity)]
+pub fn destroy_particle_effects(
+    mut commands: Commands,
+    transient_effects: Query<
+        Entity,
+        Or<(With<Shockwave>, With<ParticleHitEffect>, With<EntityDeath>)>,
+    >,
+) {
+    for effect in transient_effects.iter() {
+        commands.entity(effect).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::{App, MinimalPlugins, NextState, OnExit, Vec3};
+
+    use super::*;
+    use crate::state::GameState;
+
+    #[test]
+    fn leaving_the_game_cleans_up_every_effect() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_state::<GameState>()
+            .add_systems(OnExit(GameState::Game), destroy_particle_effects);
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Game);
+        app.update();
+        let baseline = app.world.entities().len();
+
+        app.world.spawn(Shockwave {
+            radius: 0.0,
+            max_radius: SHOCKWAVE_MAX_RADIUS,
+            timer: SHOCKWAVE_DURATION_S,
+        });
+        app.world.spawn(ParticleHitEffect {
+            position: Vec3::ZERO,
+            is_large: false,
+        });
+        app.world.spawn(EntityDeath {
+            position: Vec3::ZERO,
+            is_player: false,
+        });
+        app.update();
+        assert_eq!(app.world.entities().len(), baseline + 3);
+
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Menu);
+        app.update();
+        assert_eq!(app.world.entities().len(), baseline);
+    }
+}
@@ -0,0 +1,203 @@
+use std::fs;
+
+use bevy::{
+    log::{error, warn},
+    prelude::{
+        default, App, AssetServer, BuildChildren, Commands, Component, DespawnRecursiveExt, Entity,
+        EventReader, NodeBundle, OnEnter, Plugin, Query, Res, ResMut, Resource, Startup,
+        TextBundle, Update,
+    },
+    text::TextStyle,
+    time::Time,
+    ui::{JustifyContent, PositionType, Style, Val},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    plugins::leaderboard::RunEndedEvent,
+    state::GameState,
+    theme::{Theme, ThemedUi},
+    GameResources,
+};
+
+const ACHIEVEMENTS_PATH: &str = "achievements.json";
+const KILLS_FOR_ACHIEVEMENT: u32 = 50;
+const TOAST_DURATION_S: f32 = 3.0;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Achievement {
+    FlawlessWave,
+    FlawlessRun,
+    Exterminator,
+    BossSlayer,
+}
+
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct Achievements {
+    pub unlocked: Vec<Achievement>,
+    // Progress for the current run, only the unlocked achievements are saved
+    #[serde(skip)]
+    pub kills: u32,
+    #[serde(skip)]
+    pub damaged_this_wave: bool,
+    #[serde(skip)]
+    pub damaged_this_run: bool,
+    #[serde(skip)]
+    pub unlocked_this_run: Vec<Achievement>,
+    // Practice runs start with every powerup, so nothing is unlocked during them
+    #[serde(skip)]
+    pub practice_run: bool,
+    #[serde(skip)]
+    pending_toasts: Vec<Achievement>,
+}
+
+#[derive(Component)]
+struct AchievementToast {
+    timer: f32,
+}
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_achievements)
+            .add_systems(OnEnter(GameState::Game), start_wave_tracking)
+            .add_systems(Update, (reset_run_progress, update_toasts));
+    }
+}
+
+impl Achievement {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Achievement::FlawlessWave => "Flawless wave",
+            Achievement::FlawlessRun => "Flawless run",
+            Achievement::Exterminator => "Exterminator",
+            Achievement::BossSlayer => "Boss slayer",
+        }
+    }
+}
+
+impl Achievements {
+    pub fn unlock(&mut self, achievement: Achievement) {
+        if self.practice_run || self.unlocked.contains(&achievement) {
+            return;
+        }
+        self.unlocked.push(achievement);
+        self.unlocked_this_run.push(achievement);
+        self.pending_toasts.push(achievement);
+        self.save();
+    }
+
+    pub fn record_player_hit(&mut self) {
+        self.damaged_this_wave = true;
+        self.damaged_this_run = true;
+    }
+
+    pub fn record_kill(&mut self, is_boss: bool) {
+        self.kills += 1;
+        if self.kills >= KILLS_FOR_ACHIEVEMENT {
+            self.unlock(Achievement::Exterminator);
+        }
+        if is_boss {
+            self.unlock(Achievement::BossSlayer);
+        }
+    }
+
+    pub fn record_wave_cleared(&mut self) {
+        if !self.damaged_this_wave {
+            self.unlock(Achievement::FlawlessWave);
+        }
+        self.damaged_this_wave = false;
+    }
+
+    pub fn record_run_completed(&mut self) {
+        if !self.damaged_this_run {
+            self.unlock(Achievement::FlawlessRun);
+        }
+    }
+
+    fn save(&self) {
+        let result = serde_json::to_string_pretty(self)
+            .map_err(|err| err.to_string())
+            .and_then(|json| fs::write(ACHIEVEMENTS_PATH, json).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            error!("Failed to save achievements: {}", err);
+        }
+    }
+}
+
+fn load_achievements(mut commands: Commands) {
+    // A missing file just means that nothing has been unlocked yet
+    let achievements = match fs::read_to_string(ACHIEVEMENTS_PATH) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|err| {
+            warn!("Failed to parse achievements, starting over: {}", err);
+            Achievements::default()
+        }),
+        Err(_) => Achievements::default(),
+    };
+    commands.insert_resource(achievements);
+}
+
+// Entering the game starts a new wave, either at the start of a run or after continuing
+fn start_wave_tracking(game: Res<GameResources>, mut achievements: ResMut<Achievements>) {
+    achievements.damaged_this_wave = false;
+    achievements.practice_run = game.practice_boss;
+}
+
+fn reset_run_progress(mut er: EventReader<RunEndedEvent>, mut achievements: ResMut<Achievements>) {
+    if er.iter().count() == 0 {
+        return;
+    }
+    achievements.kills = 0;
+    achievements.damaged_this_run = false;
+    achievements.unlocked_this_run.clear();
+}
+
+fn update_toasts(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+    time: Res<Time>,
+    mut achievements: ResMut<Achievements>,
+    mut toasts: Query<(Entity, &mut AchievementToast)>,
+) {
+    for (entity, mut toast) in toasts.iter_mut() {
+        toast.timer -= time.delta_seconds();
+        if toast.timer <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    // Show one toast at a time
+    if !toasts.is_empty() || achievements.pending_toasts.is_empty() {
+        return;
+    }
+    let achievement = achievements.pending_toasts.remove(0);
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(60.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section(
+                    format!("Achievement unlocked: {}", achievement.name()),
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 30.0,
+                        color: theme.text,
+                    },
+                ))
+                .insert(ThemedUi {});
+        })
+        .insert(AchievementToast {
+            timer: TOAST_DURATION_S,
+        });
+}
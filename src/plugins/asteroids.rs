@@ -0,0 +1,364 @@
+use bevy::{
+    prelude::{
+        in_state, shape, App, Assets, Color, Commands, Component, DespawnRecursiveExt, Entity,
+        EulerRot, EventWriter, IntoSystemConfigs, Mesh, OnEnter, OnExit, PbrBundle, Plugin, Quat,
+        Query, Res, ResMut, Resource, StandardMaterial, Transform, Update, Vec3, With, Without,
+    },
+    time::Time,
+};
+use bevy_rapier3d::prelude::{Collider, GravityScale, RapierContext, RigidBody, Sensor, Velocity};
+use rand::Rng;
+
+use crate::{
+    camera::CameraShakeEvent,
+    combat::{
+        capped_delta_seconds, Bullet, Damageable, EntityDeath, ParticleHitEffect, PlayerHitEvent,
+        PlayfieldBounds,
+    },
+    countdown::StartCountdown,
+    enemy::Enemy,
+    plugins::{achievements::Achievements, tutorial::Tutorial},
+    state::GameState,
+    GameResources,
+};
+
+const ASTEROID_SPAWN_INTERVAL_MIN_S: f32 = 6.0;
+const ASTEROID_SPAWN_INTERVAL_MAX_S: f32 = 11.0;
+// Spawned above the enemy formation so they drift in from the top of the screen
+const ASTEROID_SPAWN_Z: f32 = -9.0;
+// Past the player's row and off the bottom of the screen
+const ASTEROID_DESPAWN_Z: f32 = 10.0;
+const ASTEROID_DRIFT_SPEED_MIN: f32 = 0.8;
+const ASTEROID_DRIFT_SPEED_MAX: f32 = 1.6;
+const ASTEROID_SIDEWAYS_DRIFT: f32 = 0.3;
+const ASTEROID_SPIN: f32 = 1.0;
+const ASTEROID_RADIUS_MIN: f32 = 0.25;
+const ASTEROID_RADIUS_MAX: f32 = 0.45;
+// Bigger rocks take more hits to break
+const ASTEROID_HEALTH_PER_RADIUS: f32 = 12.0;
+const ASTEROID_CONTACT_DAMAGE: u32 = 1;
+const ASTEROID_COLOR: Color = Color::rgb(0.35, 0.3, 0.27);
+
+const DEBRIS_PIECES: usize = 8;
+const DEBRIS_SIZE: f32 = 0.08;
+const DEBRIS_SPEED: f32 = 3.0;
+const DEBRIS_LIFETIME_S: f32 = 0.6;
+
+// A neutral hazard that drifts down the field, absorbing bullets from both sides
+#[derive(Component)]
+pub struct Asteroid {
+    pub health: u32,
+}
+
+// Rock fragments thrown out when an asteroid breaks apart
+#[derive(Component)]
+struct Debris {
+    velocity: Vec3,
+    time_left: f32,
+}
+
+#[derive(Resource, Default)]
+struct AsteroidSpawner {
+    timer: f32,
+}
+
+pub struct AsteroidPlugin;
+
+impl Plugin for AsteroidPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AsteroidSpawner>()
+            .add_systems(OnEnter(GameState::Game), reset_spawner)
+            .add_systems(OnExit(GameState::Game), destroy_asteroids)
+            .add_systems(
+                Update,
+                (
+                    spawn_asteroids,
+                    asteroid_bullet_hits,
+                    asteroid_player_hits,
+                    asteroid_enemy_hits,
+                    despawn_offscreen_asteroids,
+                    update_debris,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
+
+// Asteroids come and go with the frame timing, so they are rolled outside of the run's seed, see
+// `GameRng`
+fn reset_spawner(mut spawner: ResMut<AsteroidSpawner>) {
+    spawner.timer =
+        rand::thread_rng().gen_range(ASTEROID_SPAWN_INTERVAL_MIN_S..ASTEROID_SPAWN_INTERVAL_MAX_S);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_asteroids(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut spawner: ResMut<AsteroidSpawner>,
+    time: Res<Time>,
+    bounds: Res<PlayfieldBounds>,
+    countdown: Option<Res<StartCountdown>>,
+    tutorial: Res<Tutorial>,
+) {
+    if countdown.is_some() || tutorial.holds_fire() {
+        return;
+    }
+
+    spawner.timer -= capped_delta_seconds(&time);
+    if spawner.timer > 0.0 {
+        return;
+    }
+    let mut rng = rand::thread_rng();
+    spawner.timer = rng.gen_range(ASTEROID_SPAWN_INTERVAL_MIN_S..ASTEROID_SPAWN_INTERVAL_MAX_S);
+
+    let radius = rng.gen_range(ASTEROID_RADIUS_MIN..ASTEROID_RADIUS_MAX);
+    let x = rng.gen_range(-bounds.half_width..bounds.half_width);
+    let velocity = Vec3::new(
+        rng.gen_range(-ASTEROID_SIDEWAYS_DRIFT..ASTEROID_SIDEWAYS_DRIFT),
+        0.0,
+        rng.gen_range(ASTEROID_DRIFT_SPEED_MIN..ASTEROID_DRIFT_SPEED_MAX),
+    );
+    let spin = Vec3::new(
+        rng.gen_range(-ASTEROID_SPIN..ASTEROID_SPIN),
+        rng.gen_range(-ASTEROID_SPIN..ASTEROID_SPIN),
+        rng.gen_range(-ASTEROID_SPIN..ASTEROID_SPIN),
+    );
+
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(
+                Mesh::try_from(shape::Icosphere {
+                    radius,
+                    subdivisions: 1,
+                })
+                .unwrap(),
+            ),
+            material: materials.add(StandardMaterial {
+                base_color: ASTEROID_COLOR,
+                perceptual_roughness: 1.0,
+                ..Default::default()
+            }),
+            transform: Transform::from_xyz(x, 0.0, ASTEROID_SPAWN_Z),
+            ..Default::default()
+        })
+        .insert(Asteroid {
+            health: (radius * ASTEROID_HEALTH_PER_RADIUS).round() as u32,
+        })
+        .insert(RigidBody::Dynamic)
+        .insert(GravityScale(0.0))
+        .insert(Collider::ball(radius))
+        .insert(Sensor)
+        .insert(Velocity {
+            linvel: velocity,
+            angvel: spin,
+        });
+}
+
+// Any bullet that touches an asteroid is absorbed, only the player's bullets chip away at it
+fn asteroid_bullet_hits(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    rapier_context: Res<RapierContext>,
+    mut ev: EventWriter<CameraShakeEvent>,
+    mut asteroids: Query<(Entity, &mut Asteroid, &Transform)>,
+    bullets: Query<(Entity, &Bullet)>,
+) {
+    for (asteroid_entity, mut asteroid, transform) in asteroids.iter_mut() {
+        for (bullet_entity, bullet) in bullets.iter() {
+            if rapier_context.intersection_pair(asteroid_entity, bullet_entity) != Some(true) {
+                continue;
+            }
+            commands.entity(bullet_entity).despawn_recursive();
+            if bullet.is_player_bullet {
+                asteroid.health = asteroid.health.saturating_sub(bullet.damage);
+            }
+        }
+
+        if asteroid.health == 0 {
+            commands.entity(asteroid_entity).despawn_recursive();
+            spawn_debris(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                transform.translation,
+            );
+            ev.send(CameraShakeEvent { intensity: 0.3 });
+        }
+    }
+}
+
+// Flying into an asteroid hurts the ship and breaks the asteroid apart
+#[allow(clippy::too_many_arguments)]
+fn asteroid_player_hits(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    rapier_context: Res<RapierContext>,
+    game: Res<GameResources>,
+    mut ev: EventWriter<CameraShakeEvent>,
+    mut player_hit: EventWriter<PlayerHitEvent>,
+    mut achievements: ResMut<Achievements>,
+    asteroids: Query<(Entity, &Transform), With<Asteroid>>,
+    mut players: Query<(&mut Damageable, &Transform), Without<Asteroid>>,
+) {
+    for (asteroid_entity, transform) in asteroids.iter() {
+        let Some(player) = game.players.iter().copied().find(|player| {
+            rapier_context.intersection_pair(*player, asteroid_entity) == Some(true)
+        }) else {
+            continue;
+        };
+        let Ok((mut damageable, player_transform)) = players.get_mut(player) else {
+            continue;
+        };
+        if damageable.health == 0 {
+            continue;
+        }
+
+        commands.entity(asteroid_entity).despawn_recursive();
+        spawn_debris(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            transform.translation,
+        );
+        ev.send(CameraShakeEvent { intensity: 0.6 });
+
+        damageable.health = damageable.health.saturating_sub(ASTEROID_CONTACT_DAMAGE);
+        player_hit.send(PlayerHitEvent {});
+        achievements.record_player_hit();
+        if damageable.health == 0 {
+            commands.entity(player).despawn_recursive();
+            commands.spawn(EntityDeath {
+                position: player_transform.translation,
+                is_player: true,
+            });
+        }
+    }
+}
+
+// Enemies flying into an asteroid get hurt just like the player. It isn't the player's doing, so
+// these deaths don't count as kills
+#[allow(clippy::type_complexity)]
+fn asteroid_enemy_hits(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    rapier_context: Res<RapierContext>,
+    asteroids: Query<(Entity, &Transform), With<Asteroid>>,
+    mut enemies: Query<(Entity, &mut Damageable, &Transform), (With<Enemy>, Without<Asteroid>)>,
+) {
+    for (asteroid_entity, transform) in asteroids.iter() {
+        // Enemies that already died this frame are still around until the commands are applied
+        let Some((enemy, mut damageable, enemy_transform)) =
+            enemies.iter_mut().find(|(enemy, damageable, _)| {
+                damageable.health > 0
+                    && rapier_context.intersection_pair(*enemy, asteroid_entity) == Some(true)
+            })
+        else {
+            continue;
+        };
+
+        commands.entity(asteroid_entity).despawn_recursive();
+        spawn_debris(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            transform.translation,
+        );
+
+        damageable.health = damageable.health.saturating_sub(ASTEROID_CONTACT_DAMAGE);
+        let enemy_died = damageable.health == 0;
+        if enemy_died {
+            commands.entity(enemy).despawn_recursive();
+            commands.spawn(EntityDeath {
+                position: enemy_transform.translation,
+                is_player: false,
+            });
+        }
+        commands.spawn(ParticleHitEffect {
+            position: enemy_transform.translation,
+            is_large: enemy_died,
+        });
+    }
+}
+
+fn spawn_debris(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+) {
+    let mut rng = rand::thread_rng();
+    let mesh = meshes.add(Mesh::from(shape::Cube { size: DEBRIS_SIZE }));
+    let material = materials.add(StandardMaterial {
+        base_color: ASTEROID_COLOR,
+        perceptual_roughness: 1.0,
+        ..Default::default()
+    });
+    for _ in 0..DEBRIS_PIECES {
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let speed = rng.gen_range(0.5..1.0) * DEBRIS_SPEED;
+        commands
+            .spawn(PbrBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                transform: Transform::from_translation(position).with_rotation(Quat::from_euler(
+                    EulerRot::XYZ,
+                    rng.gen_range(0.0..std::f32::consts::TAU),
+                    rng.gen_range(0.0..std::f32::consts::TAU),
+                    0.0,
+                )),
+                ..Default::default()
+            })
+            .insert(Debris {
+                velocity: Vec3::new(angle.cos(), 0.0, angle.sin()) * speed,
+                time_left: DEBRIS_LIFETIME_S,
+            });
+    }
+}
+
+// Asteroids leave through the bottom of the screen, or the sides if they drift far enough
+fn despawn_offscreen_asteroids(
+    mut commands: Commands,
+    bounds: Res<PlayfieldBounds>,
+    asteroids: Query<(Entity, &Transform), With<Asteroid>>,
+) {
+    for (asteroid_entity, transform) in asteroids.iter() {
+        let translation = transform.translation;
+        if translation.z > ASTEROID_DESPAWN_Z || translation.x.abs() > bounds.half_width * 2.0 {
+            commands.entity(asteroid_entity).despawn_recursive();
+        }
+    }
+}
+
+// Fragments fly outwards and shrink away
+fn update_debris(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut debris: Query<(Entity, &mut Debris, &mut Transform)>,
+) {
+    let delta_time = capped_delta_seconds(&time);
+    for (debris_entity, mut piece, mut transform) in debris.iter_mut() {
+        piece.time_left -= delta_time;
+        if piece.time_left <= 0.0 {
+            commands.entity(debris_entity).despawn_recursive();
+            continue;
+        }
+        transform.translation += piece.velocity * delta_time;
+        transform.scale = Vec3::splat(piece.time_left / DEBRIS_LIFETIME_S);
+    }
+}
+
+fn destroy_asteroids(
+    mut commands: Commands,
+    asteroids: Query<Entity, With<Asteroid>>,
+    debris: Query<Entity, With<Debris>>,
+) {
+    for entity in asteroids.iter().chain(debris.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,282 @@
+use bevy::{
+    prelude::{
+        in_state, Assets, Color, Commands, Component, DespawnRecursiveExt, Entity, EventWriter,
+        IntoSystemConfigs, Mesh, Plugin, Query, Res, ResMut, StandardMaterial, Transform, Update,
+        Vec3, With, Without,
+    },
+    time::Time,
+};
+
+use crate::{
+    camera::CameraShakeEvent,
+    combat::{spawn_bullet, Bullet, BulletTrailAssets, Damageable, ENEMY_BULLET_DIRECTION},
+    countdown::StartCountdown,
+    damage_flash::DamageFlash,
+    enemy::Enemy,
+    plugins::enemy_wave_plugin::{live_enemy_bullets, EnemyFireLimits, MoveToTarget, Telegraphing},
+    settings::Settings,
+    state::GameState,
+    theme::Theme,
+    GameResources,
+};
+
+// How long the boss sticks to one pattern before moving on to the next one in its phase
+const BOSS_PATTERN_DURATION_S: f32 = 4.0;
+// Breather between phases, after the screen has been cleared of the boss' bullets
+const BOSS_PHASE_TRANSITION_PAUSE_S: f32 = 1.5;
+const BOSS_PHASE_TRANSITION_SHAKE: f32 = 1.5;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BulletPattern {
+    // Evenly spaced bullets in every direction, offset by half a gap every other volley
+    Ring { bullets: u32 },
+    // Streams that rotate around the boss with every shot
+    Spiral { arms: u32, degrees_per_shot: f32 },
+    // A fan of bullets centered on the player
+    AimedBurst { bullets: u32, spread_degrees: f32 },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BossPhase {
+    Opening,
+    Enraged,
+    Desperate,
+}
+
+#[derive(Component)]
+pub struct BossAttackState {
+    pub max_health: u32,
+    pub phase: BossPhase,
+    pub pattern_index: usize,
+    pub pattern_timer: f32,
+    pub fire_timer: f32,
+    // Accumulated rotation in degrees, used by the ring and spiral patterns
+    pub angle: f32,
+}
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_systems(
+            Update,
+            update_boss_attacks.run_if(in_state(GameState::Game)),
+        );
+    }
+}
+
+impl BossPhase {
+    pub fn from_health(health: u32, max_health: u32) -> BossPhase {
+        let fraction = health as f32 / max_health.max(1) as f32;
+        if fraction > 2.0 / 3.0 {
+            BossPhase::Opening
+        } else if fraction > 1.0 / 3.0 {
+            BossPhase::Enraged
+        } else {
+            BossPhase::Desperate
+        }
+    }
+
+    // The patterns are cycled through in order while the boss is in this phase
+    pub fn patterns(&self) -> &'static [BulletPattern] {
+        match self {
+            BossPhase::Opening => &[
+                BulletPattern::AimedBurst {
+                    bullets: 3,
+                    spread_degrees: 30.0,
+                },
+                BulletPattern::Ring { bullets: 8 },
+            ],
+            BossPhase::Enraged => &[
+                BulletPattern::Spiral {
+                    arms: 2,
+                    degrees_per_shot: 17.0,
+                },
+                BulletPattern::AimedBurst {
+                    bullets: 5,
+                    spread_degrees: 45.0,
+                },
+                BulletPattern::Ring { bullets: 12 },
+            ],
+            BossPhase::Desperate => &[
+                BulletPattern::Spiral {
+                    arms: 3,
+                    degrees_per_shot: 13.0,
+                },
+                BulletPattern::Ring { bullets: 16 },
+                BulletPattern::AimedBurst {
+                    bullets: 7,
+                    spread_degrees: 60.0,
+                },
+            ],
+        }
+    }
+
+    pub fn fire_interval(&self) -> f32 {
+        match self {
+            BossPhase::Opening => 0.9,
+            BossPhase::Enraged => 0.6,
+            BossPhase::Desperate => 0.4,
+        }
+    }
+}
+
+impl BossAttackState {
+    pub fn new(max_health: u32) -> Self {
+        Self {
+            max_health,
+            phase: BossPhase::Opening,
+            pattern_index: 0,
+            pattern_timer: BOSS_PATTERN_DURATION_S,
+            fire_timer: BossPhase::Opening.fire_interval(),
+            angle: 0.0,
+        }
+    }
+
+    pub fn phase(&self, health: u32) -> BossPhase {
+        BossPhase::from_health(health, self.max_health)
+    }
+}
+
+// Direction in the playfield for an angle in degrees, where 0 points straight down at the player
+fn direction_from_angle(degrees: f32) -> Vec3 {
+    let radians = degrees.to_radians();
+    Vec3::new(radians.sin(), 0.0, radians.cos())
+}
+
+fn pattern_directions(pattern: BulletPattern, angle: &mut f32, aim: Vec3) -> Vec<Vec3> {
+    match pattern {
+        BulletPattern::Ring { bullets } => {
+            let gap = 360.0 / bullets as f32;
+            let directions = (0..bullets)
+                .map(|i| direction_from_angle(*angle + i as f32 * gap))
+                .collect();
+            *angle += gap / 2.0;
+            directions
+        }
+        BulletPattern::Spiral {
+            arms,
+            degrees_per_shot,
+        } => {
+            let gap = 360.0 / arms as f32;
+            let directions = (0..arms)
+                .map(|i| direction_from_angle(*angle + i as f32 * gap))
+                .collect();
+            *angle += degrees_per_shot;
+            directions
+        }
+        BulletPattern::AimedBurst {
+            bullets,
+            spread_degrees,
+        } => {
+            let aim_angle = f32::atan2(aim.x, aim.z).to_degrees();
+            if bullets <= 1 {
+                return vec![direction_from_angle(aim_angle)];
+            }
+
+            let step = spread_degrees / (bullets - 1) as f32;
+            (0..bullets)
+                .map(|i| direction_from_angle(aim_angle - spread_degrees / 2.0 + i as f32 * step))
+                .collect()
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_boss_attacks(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    theme: Res<Theme>,
+    trail: Res<BulletTrailAssets>,
+    time: Res<Time>,
+    game: Res<GameResources>,
+    countdown: Option<Res<StartCountdown>>,
+    settings: Res<Settings>,
+    fire_limits: Res<EnemyFireLimits>,
+    mut flash: ResMut<DamageFlash>,
+    mut shake: EventWriter<CameraShakeEvent>,
+    bullets: Query<(Entity, &Bullet)>,
+    telegraphs: Query<(), With<Telegraphing>>,
+    players: Query<&Transform, Without<Enemy>>,
+    mut bosses: Query<
+        (&mut BossAttackState, &Damageable, &Transform),
+        (With<Enemy>, Without<MoveToTarget>),
+    >,
+) {
+    // Hold fire until the start countdown is over
+    if countdown.is_some() {
+        return;
+    }
+
+    let mut live_bullets = live_enemy_bullets(
+        bullets.iter().map(|(_, bullet)| bullet),
+        telegraphs.iter().count(),
+    );
+    let delta_time = time.delta_seconds() * settings.game_speed.0;
+    for (mut state, damageable, transform) in bosses.iter_mut() {
+        let phase = state.phase(damageable.health);
+        if phase != state.phase {
+            // Wipe the screen and give the player a moment before the next phase begins
+            for (bullet_entity, bullet) in bullets.iter() {
+                if !bullet.is_player_bullet {
+                    commands.entity(bullet_entity).despawn_recursive();
+                }
+            }
+            shake.send(CameraShakeEvent {
+                intensity: BOSS_PHASE_TRANSITION_SHAKE,
+            });
+            flash.trigger(Color::WHITE);
+            state.phase = phase;
+            state.pattern_index = 0;
+            state.pattern_timer = BOSS_PATTERN_DURATION_S;
+            state.fire_timer = BOSS_PHASE_TRANSITION_PAUSE_S;
+            state.angle = 0.0;
+            continue;
+        }
+
+        state.pattern_timer -= delta_time;
+        if state.pattern_timer <= 0.0 {
+            state.pattern_index += 1;
+            state.pattern_timer = BOSS_PATTERN_DURATION_S;
+        }
+
+        state.fire_timer -= delta_time;
+        if state.fire_timer > 0.0 {
+            continue;
+        }
+        // Hold the volley while the screen is full, it goes off as soon as there is room again
+        let room = fire_limits.max_live_bullets.saturating_sub(live_bullets);
+        if room == 0 {
+            continue;
+        }
+        state.fire_timer = phase.fire_interval();
+
+        let patterns = phase.patterns();
+        let pattern = patterns[state.pattern_index % patterns.len()];
+        // Aimed bursts go for the nearest ship, or straight down with no player left to aim at
+        let aim = game
+            .nearest_player(transform.translation, &players)
+            .map(|position| (position - transform.translation).normalize_or_zero())
+            .filter(|aim| *aim != Vec3::ZERO)
+            .unwrap_or(ENEMY_BULLET_DIRECTION);
+
+        // Volleys bigger than the room that is left are cut short
+        for direction in pattern_directions(pattern, &mut state.angle, aim)
+            .into_iter()
+            .take(room)
+        {
+            live_bullets += 1;
+            spawn_bullet(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                &theme,
+                &trail,
+                transform.translation,
+                direction,
+                false,
+            );
+        }
+    }
+}
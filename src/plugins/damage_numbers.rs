@@ -0,0 +1,119 @@
+use bevy::{
+    prelude::{
+        default, in_state, Added, App, AssetServer, Camera, Commands, Component,
+        DespawnRecursiveExt, Entity, GlobalTransform, IntoSystemConfigs, OnExit, Plugin, Query,
+        Res, TextBundle, Update, Vec3, Visibility, With,
+    },
+    text::{Text, TextStyle},
+    time::Time,
+    ui::{PositionType, Style, UiScale, Val},
+};
+
+use crate::{camera::world_to_ui_position, state::GameState, theme::Theme};
+
+pub const DAMAGE_NUMBER_DURATION_S: f32 = 0.8;
+// Numbers float up the screen, towards -z, while they fade out
+const DAMAGE_NUMBER_RISE_VELOCITY: f32 = 1.2;
+const DAMAGE_NUMBER_FONT_SIZE: f32 = 22.0;
+// Hits above the base damage of one are shown larger, the biggest hits stand out the most
+const DAMAGE_NUMBER_FONT_SIZE_PER_DAMAGE: f32 = 8.0;
+const DAMAGE_NUMBER_MAX_FONT_SIZE: f32 = 48.0;
+
+// Floating combat text for a single hit, spawned where the bullet hit when damage numbers are on
+#[derive(Component)]
+pub struct DamageNumber {
+    pub value: u32,
+    pub timer: f32,
+    pub position: Vec3,
+}
+
+pub struct DamageNumberPlugin;
+
+impl Plugin for DamageNumberPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnExit(GameState::Game), destroy_damage_numbers)
+            .add_systems(
+                Update,
+                (show_damage_numbers, update_damage_numbers)
+                    .chain()
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
+
+fn show_damage_numbers(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+    numbers: Query<(Entity, &DamageNumber), Added<DamageNumber>>,
+) {
+    for (number_entity, number) in numbers.iter() {
+        let font_size = (DAMAGE_NUMBER_FONT_SIZE
+            + number.value.saturating_sub(1) as f32 * DAMAGE_NUMBER_FONT_SIZE_PER_DAMAGE)
+            .min(DAMAGE_NUMBER_MAX_FONT_SIZE);
+        // Placed on the screen by `update_damage_numbers`, hidden until then
+        commands.entity(number_entity).insert(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..TextBundle::from_section(
+                number.value.to_string(),
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size,
+                    color: theme.text,
+                },
+            )
+        });
+    }
+}
+
+fn update_damage_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    ui_scale: Res<UiScale>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut numbers: Query<(
+        Entity,
+        &mut DamageNumber,
+        &mut Style,
+        &mut Text,
+        &mut Visibility,
+    )>,
+) {
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+
+    for (number_entity, mut number, mut style, mut text, mut visibility) in numbers.iter_mut() {
+        number.timer -= time.delta_seconds();
+        if number.timer <= 0.0 {
+            commands.entity(number_entity).despawn_recursive();
+            continue;
+        }
+        number.position.z -= DAMAGE_NUMBER_RISE_VELOCITY * time.delta_seconds();
+
+        let Some(screen_position) =
+            world_to_ui_position(camera, camera_transform, &ui_scale, number.position)
+        else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        style.left = Val::Px(screen_position.x);
+        style.top = Val::Px(screen_position.y);
+        *visibility = Visibility::Inherited;
+
+        let alpha = number.timer / DAMAGE_NUMBER_DURATION_S;
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(alpha);
+        }
+    }
+}
+
+fn destroy_damage_numbers(mut commands: Commands, numbers: Query<Entity, With<DamageNumber>>) {
+    for number in numbers.iter() {
+        commands.entity(number).despawn_recursive();
+    }
+}
@@ -1,12 +1,13 @@
 use std::ops::{Mul, Sub};
 
 use bevy::{
+    log::info,
     prelude::{
-        default, in_state, AssetServer, Assets, BuildChildren, Color, Commands, Component,
-        DespawnRecursiveExt, Entity, Event, EventReader, EventWriter, IntoSystemConfigs, Mesh,
-        NextState, NodeBundle, OnEnter, OnExit, Plugin, Query, Res, ResMut, Resource,
-        SpatialBundle, StandardMaterial, Startup, TextBundle, Transform, Update, Vec3, With,
-        Without,
+        default, in_state, shape, Added, AlphaMode, AssetServer, Assets, BuildChildren, Children,
+        Color, Commands, Component, DespawnRecursiveExt, Entity, Event, EventReader, EventWriter,
+        Handle, HierarchyQueryExt, IntoSystemConfigs, Mesh, NextState, NodeBundle, OnEnter, OnExit,
+        PbrBundle, Plugin, Quat, Query, Res, ResMut, Resource, SpatialBundle, StandardMaterial,
+        TextBundle, Transform, Update, Vec3, Visibility, With, Without,
     },
     scene::SceneBundle,
     text::{Text, TextStyle},
@@ -17,20 +18,82 @@ use bevy_rapier3d::prelude::{ActiveEvents, Collider, GravityScale, RigidBody, Se
 use rand::Rng;
 
 use crate::{
-    combat::{spawn_bullet, Damageable},
+    combat::{
+        capped_delta_seconds, spawn_bullet, Bouncing, Bullet, BulletTrailAssets, Damageable,
+        DirectionalShield, PlayfieldBounds, ENEMY_BULLET_DIRECTION,
+    },
+    countdown::StartCountdown,
     enemy::Enemy,
+    plugins::{
+        achievements::Achievements,
+        boss::BossAttackState,
+        leaderboard::RunEndedEvent,
+        mines::MineLayer,
+        rewards::{update_reward_buttons, RewardOffer, REWARD_CHANCE},
+        support::Support,
+        tutorial::{start_tutorial, update_tutorial, Tutorial},
+    },
+    rng::GameRng,
+    settings::Settings,
     state::GameState,
+    theme::{Theme, ThemedUi},
+    GameResources,
 };
 
-const ENEMY_COOLDOWN_RANGE_S: (f32, f32) = (2.0, 3.0);
-const ENEMY_FIRE_PROBABILITY: f32 = 0.5;
 const ENEMY_MOVE_DURATION_S: f32 = 2.0;
 const ENEMY_MOVE_VELOCITY: f32 = 0.75;
+const ENEMY_TRACKING_MAX_VELOCITY: f32 = 0.6;
+const ENEMY_TRACKING_GAIN: f32 = 1.5;
+const MAX_LIVE_ENEMY_BULLETS: usize = 12;
+const ENEMY_SPAWN_FADE_DURATION_S: f32 = 0.3;
+const BOSS_SCALE: f32 = 2.5;
+// Formations are squeezed together to keep this much room to the playfield edges, for the
+// left/right sweep and the ships themselves
+const FORMATION_EDGE_MARGIN: f32 = 1.5;
+const MAX_FORMATION_X_SPACING: f32 = 0.5;
+// Enemies fly in from up to this many playfield half-widths to either side
+const SPAWN_SCATTER_HALF_WIDTHS: f32 = 1.5;
+// Enemies flash an indicator for this long before their shot goes off
+const ENEMY_TELEGRAPH_DURATION_S: f32 = 0.3;
+const TELEGRAPH_BLINK_INTERVAL_S: f32 = 0.06;
+// The indicator sits just in front of the ship, towards the player
+const TELEGRAPH_INDICATOR_OFFSET: Vec3 = Vec3::new(0.0, 0.0, 0.4);
+// Bouncing shots leave at a random angle in this range (in degrees) to either side of straight down
+const BOUNCING_SHOT_ANGLE_RANGE_DEG: (f32, f32) = (20.0, 40.0);
+// Shields keep turning around their enemy, opening a window to hit it every turn
+const SHIELD_TURN_RATE_DEG: f32 = 60.0;
+const SHIELD_PLATE_DISTANCE: f32 = 0.45;
+// Swooping entries fly in one after the other, each taking this long to reach its spot
+const ENTRY_PATH_DURATION_S: f32 = 1.6;
+const ENTRY_STAGGER_S: f32 = 0.12;
+// Swoops start this far past the playfield edge and curve down towards the player on the way in
+const ENTRY_EDGE_OFFSET: f32 = 2.0;
+const ENTRY_START_Z: f32 = -6.0;
+const ENTRY_SWOOP_Z: f32 = 5.0;
 
 pub struct EnemyWavePlugin;
 
 pub struct Wave {
     enemies: Vec<EnemyInstance>,
+    // Scales the health of every enemy in the wave, so later waves can be tankier
+    health_multiplier: f32,
+    entry: EntryStyle,
+}
+
+// How the enemies of a wave make their way to their spots in the formation
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EntryStyle {
+    // Straight in from random points above the playfield
+    Scatter,
+    // One after the other along a curve that enters from a side of the playfield
+    Swoop { from: EntrySide },
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EntrySide {
+    Left,
+    // Each enemy enters from the side of the playfield its spot is on
+    Nearest,
 }
 
 #[derive(Event)]
@@ -45,25 +108,92 @@ pub struct EnemyAIState {
     pub moving_left: bool,
 }
 
+// Caps how many enemy bullets may be alive at once so large waves can't flood the screen
+#[derive(Resource)]
+pub struct EnemyFireLimits {
+    pub max_live_bullets: usize,
+}
+
 struct EnemyInstance {
-    // Positions are given in a 2D grid, where (0, 0) is in the center of the screen
-    position: [i32; 2],
+    // Positions are given in a 2D grid, where (0, 0) is in the center of the screen, see
+    // `Formation` for laying them out in shapes
+    position: [f32; 2],
     ship_type: EnemyType,
     health: u32,
+    // Trackers follow the player's x-position instead of sweeping with the formation
+    tracking: bool,
+    // Bosses are drawn larger and fire bullet patterns instead of single shots
+    boss: bool,
+    // When above zero, shots are fired at an angle and bounce off the walls this many times
+    bullet_bounces: u32,
+    // Shielded enemies block bullets from one side, see `DirectionalShield`
+    shielded: bool,
+    // Mine layers drop stationary mines in front of the player, see `MineLayer`
+    lays_mines: bool,
+    // Supports heal damaged enemies around them, see `Support`
+    support: bool,
+}
+
+#[derive(Component)]
+pub struct BouncingShots {
+    bounces: u32,
+}
+
+// Added shortly before an enemy fires, the shot goes off once the timer runs out
+#[derive(Component)]
+pub struct Telegraphing {
+    timer: f32,
+    indicator: Entity,
 }
 
 #[derive(Component)]
-struct MoveToTarget {
+pub struct MoveToTarget {
     target: Vec3,
 }
 
+// A quadratic bezier curve flown before `MoveToTarget` takes over for the last bit
+#[derive(Component)]
+struct EntryPath {
+    start: Vec3,
+    control: Vec3,
+    end: Vec3,
+    delay: f32,
+    progress: f32,
+}
+
+impl EntryPath {
+    fn point(&self, t: f32) -> Vec3 {
+        let u = 1.0 - t;
+        self.start * (u * u) + self.control * (2.0 * u * t) + self.end * (t * t)
+    }
+}
+
+#[derive(Component)]
+struct SpawnFade {
+    timer: f32,
+}
+
+// Fading enemies get their own copy of each scene material, since the loaded model shares
+// its materials between every ship of the same type
+#[derive(Component)]
+struct FadeMaterial {
+    original_alpha: f32,
+    original_alpha_mode: AlphaMode,
+}
+
+#[derive(Component)]
+pub struct Tracking {
+    pub max_velocity: f32,
+}
+
 #[derive(Component)]
 struct WaveUI {}
 
 #[derive(Component)]
 struct RootWaveUI {}
 
-enum EnemyType {
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EnemyType {
     Type1,
     Type2,
     Type3,
@@ -72,7 +202,11 @@ enum EnemyType {
 impl Plugin for EnemyWavePlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.add_event::<NewWaveEvent>()
-            .add_systems(OnEnter(GameState::Game), (init_enemy_waves, init_ui))
+            .init_resource::<EnemyFireLimits>()
+            .add_systems(
+                OnEnter(GameState::Game),
+                (init_enemy_waves.after(start_tutorial), init_ui),
+            )
             .add_systems(
                 OnExit(GameState::Game),
                 (destroy_ui, destroy_enemies, reset_ai_state),
@@ -81,8 +215,17 @@ impl Plugin for EnemyWavePlugin {
                 Update,
                 (
                     update_enemies,
+                    update_tracking_enemies.after(update_enemies),
+                    update_telegraphs.after(update_enemies),
+                    attach_shield_plates,
+                    update_shields,
+                    follow_entry_paths.before(update_move_to_target),
                     update_move_to_target,
-                    change_wave,
+                    update_spawn_fade,
+                    // The tutorial spawns the first wave itself once it is done
+                    change_wave
+                        .before(update_tutorial)
+                        .before(update_reward_buttons),
                     update_ui,
                 )
                     .run_if(in_state(GameState::Game)),
@@ -95,14 +238,35 @@ fn init_enemy_waves(
     mut ev: EventWriter<NewWaveEvent>,
     asset_server: Res<AssetServer>,
     ai_state: Res<EnemyAIState>,
+    bounds: Res<PlayfieldBounds>,
+    tutorial: Res<Tutorial>,
+    mut rng: ResMut<GameRng>,
 ) {
     ev.send(NewWaveEvent {
         wave: ai_state.current_wave,
     });
-    spawn_wave(0, commands, asset_server);
+    rng.start_wave(ai_state.current_wave);
+    if tutorial.is_active() {
+        spawn_enemies(
+            &get_tutorial_wave(),
+            &bounds,
+            &mut rng,
+            commands,
+            asset_server,
+        );
+        return;
+    }
+    // Usually the first wave, but a continued run resumes from the wave the player died on
+    spawn_wave(
+        ai_state.current_wave as usize,
+        &bounds,
+        &mut rng,
+        commands,
+        asset_server,
+    );
 }
 
-fn init_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn init_ui(mut commands: Commands, asset_server: Res<AssetServer>, theme: Res<Theme>) {
     commands
         .spawn(NodeBundle {
             style: Style { ..default() },
@@ -116,7 +280,7 @@ fn init_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                         TextStyle {
                             font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                             font_size: 45.0,
-                            color: Color::WHITE,
+                            color: theme.text,
                         },
                     )
                     .with_style(Style {
@@ -124,7 +288,8 @@ fn init_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                         ..default()
                     }),
                 )
-                .insert(WaveUI {});
+                .insert(WaveUI {})
+                .insert(ThemedUi {});
         })
         .insert(RootWaveUI {});
 }
@@ -141,118 +306,439 @@ fn destroy_enemies(mut commands: Commands, enemies: Query<Entity, With<Enemy>>)
     }
 }
 
-fn reset_ai_state(mut state: ResMut<EnemyAIState>) {
+pub fn reset_ai_state(mut state: ResMut<EnemyAIState>) {
     *state = EnemyAIState::default();
 }
 
-fn spawn_wave(wave_id: usize, mut commands: Commands, asset_server: Res<AssetServer>) {
+pub fn spawn_wave(
+    wave_id: usize,
+    bounds: &PlayfieldBounds,
+    rng: &mut GameRng,
+    commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
     let waves = get_waves();
-    let wave = waves.get(wave_id).unwrap();
+    spawn_enemies(
+        waves.get(wave_id).unwrap(),
+        bounds,
+        rng,
+        commands,
+        asset_server,
+    );
+}
 
+fn spawn_enemies(
+    wave: &Wave,
+    bounds: &PlayfieldBounds,
+    rng: &mut GameRng,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
     let z_starting_pos_offset = -3.0;
-    let x_spacing = 0.5;
+    let x_spacing = formation_x_spacing(wave, bounds);
     let z_spacing = 1.0;
+    let scatter = bounds.half_width * SPAWN_SCATTER_HALF_WIDTHS;
 
-    let mut rng = rand::thread_rng();
-
-    for enemy in wave.enemies.iter() {
-        commands
+    for (index, enemy) in wave.enemies.iter().enumerate() {
+        let scale = if enemy.boss { BOSS_SCALE } else { 1.0 };
+        let health = wave.enemy_health(enemy);
+        let target = Vec3::new(
+            enemy.position[0] * x_spacing,
+            0.,
+            enemy.position[1] * z_spacing + z_starting_pos_offset,
+        );
+        let entry_path = match wave.entry {
+            EntryStyle::Scatter => None,
+            EntryStyle::Swoop { from } => {
+                let side = match from {
+                    EntrySide::Left => -1.0,
+                    EntrySide::Nearest if target.x < 0.0 => -1.0,
+                    EntrySide::Nearest => 1.0,
+                };
+                Some(EntryPath {
+                    start: Vec3::new(
+                        side * (bounds.half_width + ENTRY_EDGE_OFFSET),
+                        0.0,
+                        ENTRY_START_Z,
+                    ),
+                    // Pulls the curve across the playfield and down towards the player
+                    control: Vec3::new(-side * bounds.half_width * 0.5, 0.0, ENTRY_SWOOP_Z),
+                    end: target,
+                    delay: index as f32 * ENTRY_STAGGER_S,
+                    progress: 0.0,
+                })
+            }
+        };
+        let start = match &entry_path {
+            Some(path) => path.start,
+            None => Vec3::new(
+                target.x + rng.waves().gen_range(-scatter..scatter),
+                0.0,
+                target.z + rng.waves().gen_range(-7.0..-1.0),
+            ),
+        };
+        let enemy_entity = commands
             .spawn(Enemy {
-                shot_cooldown_timer: rng
-                    .gen_range(ENEMY_COOLDOWN_RANGE_S.0..=ENEMY_COOLDOWN_RANGE_S.1),
+                shot_cooldown_timer: enemy.ship_type.roll_cooldown(rng.waves()),
+                enemy_type: enemy.ship_type,
             })
             .insert(Velocity::default())
             .insert(SpatialBundle {
-                transform: Transform::from_translation(Vec3::new(
-                    enemy.position[0] as f32 * x_spacing + rng.gen_range(-7.0..7.0),
-                    0.0,
-                    enemy.position[1] as f32 * z_spacing
-                        + z_starting_pos_offset
-                        + rng.gen_range(-7.0..-1.0),
-                )),
+                transform: Transform::from_translation(start),
                 ..Default::default()
             })
             .insert(Damageable {
-                health: enemy.health,
+                health,
+                max_health: health,
                 is_player: false,
             })
-            .insert(MoveToTarget {
-                target: Vec3::new(
-                    enemy.position[0] as f32 * x_spacing,
-                    0.,
-                    enemy.position[1] as f32 * z_spacing + z_starting_pos_offset,
-                ),
-            })
+            .insert(MoveToTarget { target })
             .insert(RigidBody::Dynamic)
             .insert(Sensor {})
             .insert(GravityScale(0.0))
-            .insert(Collider::cylinder(0.25, 0.3))
+            .insert(enemy.ship_type.get_collider(scale))
             .insert(ActiveEvents::COLLISION_EVENTS)
+            .insert(SpawnFade {
+                timer: ENEMY_SPAWN_FADE_DURATION_S,
+            })
             .with_children(|children| {
                 children.spawn(SceneBundle {
                     transform: Transform {
-                        scale: Vec3::new(0.001, 0.001, 0.001),
+                        scale: Vec3::splat(0.001 * scale),
                         ..Default::default()
                     },
                     scene: asset_server.load(enemy.ship_type.get_ship_path()),
                     ..Default::default()
                 });
+            })
+            .id();
+
+        if enemy.tracking {
+            commands.entity(enemy_entity).insert(Tracking {
+                max_velocity: ENEMY_TRACKING_MAX_VELOCITY,
+            });
+        }
+        if enemy.boss {
+            commands
+                .entity(enemy_entity)
+                .insert(BossAttackState::new(health));
+        }
+        if enemy.shielded {
+            commands.entity(enemy_entity).insert(DirectionalShield {
+                facing: ENEMY_BULLET_DIRECTION,
+            });
+        }
+        if enemy.bullet_bounces > 0 {
+            commands.entity(enemy_entity).insert(BouncingShots {
+                bounces: enemy.bullet_bounces,
             });
+        }
+        if enemy.lays_mines {
+            commands.entity(enemy_entity).insert(MineLayer::default());
+        }
+        if enemy.support {
+            commands.entity(enemy_entity).insert(Support::default());
+        }
+        if let Some(entry_path) = entry_path {
+            commands.entity(enemy_entity).insert(entry_path);
+        }
+    }
+}
+
+// Spacing between grid columns, narrowed so that the widest column still fits on the screen
+fn formation_x_spacing(wave: &Wave, bounds: &PlayfieldBounds) -> f32 {
+    let widest_column = wave
+        .enemies
+        .iter()
+        .map(|enemy| enemy.position[0].abs())
+        .fold(0.0, f32::max);
+    if widest_column == 0.0 {
+        return MAX_FORMATION_X_SPACING;
     }
+
+    let usable_half_width = (bounds.half_width - FORMATION_EDGE_MARGIN).max(0.0);
+    f32::min(usable_half_width / widest_column, MAX_FORMATION_X_SPACING)
+}
+
+// Enemy bullets in flight plus the shots that are about to be fired, checked against
+// `EnemyFireLimits` by both the wave enemies and the boss
+pub fn live_enemy_bullets<'a>(
+    bullets: impl Iterator<Item = &'a Bullet>,
+    telegraphed_shots: usize,
+) -> usize {
+    bullets.filter(|bullet| !bullet.is_player_bullet).count() + telegraphed_shots
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn update_enemies(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut ai_state: ResMut<EnemyAIState>,
+    fire_limits: Res<EnemyFireLimits>,
+    countdown: Option<Res<StartCountdown>>,
+    tutorial: Res<Tutorial>,
+    theme: Res<Theme>,
+    trail: Res<BulletTrailAssets>,
     time: Res<Time>,
-    mut enemies: Query<(&mut Enemy, &mut Velocity, &Transform), Without<MoveToTarget>>,
+    settings: Res<Settings>,
+    mut enemies: Query<
+        (
+            Entity,
+            &mut Enemy,
+            &mut Velocity,
+            &Transform,
+            Option<&BossAttackState>,
+            Option<&BouncingShots>,
+            Option<&mut Telegraphing>,
+        ),
+        Without<MoveToTarget>,
+    >,
     move_to_target: Query<Entity, With<MoveToTarget>>,
+    bullets: Query<&Bullet>,
 ) {
+    // When enemies get to fire depends on the frame timing, so it can't be part of the run's seed
+    let mut rng = rand::thread_rng();
     // Ensure all (non-dead) enemies have finished moving to the target position before
     // initiating left/right movement
     if !move_to_target.is_empty() {
         return;
     }
-    ai_state.move_timer -= time.delta_seconds();
+    let delta_time = capped_delta_seconds(&time) * settings.game_speed.0;
+    ai_state.move_timer -= delta_time;
     if ai_state.move_timer <= 0.0 {
         // Swap direction
         ai_state.moving_left = !ai_state.moving_left;
         ai_state.move_timer = ENEMY_MOVE_DURATION_S;
     }
 
-    let mut rng = rand::thread_rng();
-    for (mut enemy, mut enemy_vel, transform) in enemies.iter_mut() {
-        enemy_vel.linvel.x = if ai_state.moving_left {
-            -ENEMY_MOVE_VELOCITY
-        } else {
-            ENEMY_MOVE_VELOCITY
-        };
+    // Telegraphed shots count towards the limit, as they are about to become bullets
+    let telegraphed_shots = enemies
+        .iter()
+        .filter(|(.., telegraph)| telegraph.is_some())
+        .count();
+    let mut live_bullets = live_enemy_bullets(bullets.iter(), telegraphed_shots);
+    for (enemy_entity, mut enemy, mut enemy_vel, transform, boss, bouncing_shots, telegraph) in
+        enemies.iter_mut()
+    {
+        let sweep_direction = if ai_state.moving_left { -1.0 } else { 1.0 };
+        enemy_vel.linvel.x = sweep_direction * ENEMY_MOVE_VELOCITY * settings.game_speed.0;
 
-        // Fire with a certain probability, otherwise skip the turn and just wait for the cooldown again
-        enemy.shot_cooldown_timer -= time.delta_seconds();
-        if enemy.shot_cooldown_timer <= 0.0 {
-            if rng.gen::<f32>() < ENEMY_FIRE_PROBABILITY {
-                // Fire!
-                spawn_bullet(
+        // Bosses fire their own patterns, see the boss plugin, and nobody fires during the countdown
+        if boss.is_some() || countdown.is_some() || tutorial.holds_fire() {
+            continue;
+        }
+
+        // Finish a telegraphed shot once the warning has been shown for long enough
+        if let Some(mut telegraph) = telegraph {
+            telegraph.timer -= delta_time;
+            if telegraph.timer <= 0.0 {
+                commands.entity(telegraph.indicator).despawn_recursive();
+                commands.entity(enemy_entity).remove::<Telegraphing>();
+                fire_enemy_shot(
                     &mut commands,
                     &mut meshes,
                     &mut materials,
+                    &theme,
+                    &trail,
                     transform.translation,
-                    false,
+                    bouncing_shots,
                 );
             }
+        }
+
+        // Fire with a certain probability, otherwise skip the turn and just wait for the cooldown again
+        enemy.shot_cooldown_timer -= delta_time;
+        if enemy.shot_cooldown_timer <= 0.0 {
+            let can_fire = live_bullets < fire_limits.max_live_bullets;
+            if can_fire && rng.gen::<f32>() < enemy.enemy_type.fire_probability() {
+                live_bullets += 1;
+                let indicator = commands
+                    .spawn(PbrBundle {
+                        mesh: meshes.add(Mesh::from(shape::UVSphere {
+                            radius: 0.08,
+                            ..Default::default()
+                        })),
+                        transform: Transform::from_translation(TELEGRAPH_INDICATOR_OFFSET),
+                        material: materials.add(StandardMaterial {
+                            emissive: theme.enemy_bullet,
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    })
+                    .id();
+                commands
+                    .entity(enemy_entity)
+                    .add_child(indicator)
+                    .insert(Telegraphing {
+                        timer: ENEMY_TELEGRAPH_DURATION_S,
+                        indicator,
+                    });
+            }
+
+            enemy.shot_cooldown_timer = enemy.enemy_type.roll_cooldown(&mut rng);
+        }
+    }
+}
+
+fn fire_enemy_shot(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    theme: &Theme,
+    trail: &BulletTrailAssets,
+    translation: Vec3,
+    bouncing_shots: Option<&BouncingShots>,
+) {
+    let direction = match bouncing_shots {
+        // Angle the shot to either side so that it actually reaches a wall
+        Some(_) => {
+            let mut rng = rand::thread_rng();
+            let angle = rng
+                .gen_range(BOUNCING_SHOT_ANGLE_RANGE_DEG.0..=BOUNCING_SHOT_ANGLE_RANGE_DEG.1)
+                * if rng.gen::<bool>() { 1.0 } else { -1.0 };
+            Quat::from_rotation_y(angle.to_radians()).mul_vec3(ENEMY_BULLET_DIRECTION)
+        }
+        None => ENEMY_BULLET_DIRECTION,
+    };
+    let bullet = spawn_bullet(
+        commands,
+        meshes,
+        materials,
+        theme,
+        trail,
+        translation,
+        direction,
+        false,
+    );
+    if let Some(bouncing_shots) = bouncing_shots {
+        commands.entity(bullet).insert(Bouncing {
+            bounces_left: bouncing_shots.bounces,
+        });
+    }
+}
+
+#[derive(Component)]
+struct ShieldPlate {}
+
+fn shield_plate_transform(facing: Vec3) -> Transform {
+    Transform::from_translation(facing * SHIELD_PLATE_DISTANCE)
+        .with_rotation(Quat::from_rotation_arc(Vec3::Z, facing))
+}
+
+// Gives newly spawned shielded enemies a visible plate on the shielded side
+fn attach_shield_plates(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    shields: Query<(Entity, &DirectionalShield), Added<DirectionalShield>>,
+) {
+    for (enemy_entity, shield) in shields.iter() {
+        let plate = commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Box::new(0.8, 0.1, 0.06))),
+                material: materials.add(StandardMaterial {
+                    base_color: Color::rgba(0.3, 0.8, 1.0, 0.6),
+                    emissive: Color::rgb_linear(0.6, 2.0, 3.0),
+                    alpha_mode: AlphaMode::Blend,
+                    unlit: true,
+                    ..Default::default()
+                }),
+                transform: shield_plate_transform(shield.facing),
+                ..Default::default()
+            })
+            .insert(ShieldPlate {})
+            .id();
+        commands.entity(enemy_entity).add_child(plate);
+    }
+}
+
+fn update_shields(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut shields: Query<(&mut DirectionalShield, &Children)>,
+    mut plates: Query<&mut Transform, With<ShieldPlate>>,
+) {
+    let turn =
+        (SHIELD_TURN_RATE_DEG * capped_delta_seconds(&time) * settings.game_speed.0).to_radians();
+    for (mut shield, children) in shields.iter_mut() {
+        shield.facing = (Quat::from_rotation_y(turn) * shield.facing).normalize();
+        for child in children.iter() {
+            if let Ok(mut transform) = plates.get_mut(*child) {
+                *transform = shield_plate_transform(shield.facing);
+            }
+        }
+    }
+}
+
+// Blinks the indicator of enemies that are about to fire
+fn update_telegraphs(telegraphs: Query<&Telegraphing>, mut indicators: Query<&mut Visibility>) {
+    for telegraph in telegraphs.iter() {
+        let Ok(mut visibility) = indicators.get_mut(telegraph.indicator) else {
+            continue;
+        };
+        let blink = ((telegraph.timer / TELEGRAPH_BLINK_INTERVAL_S) as u32).is_multiple_of(2);
+        *visibility = if blink {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+// Overrides the formation sweep for trackers, steering them towards the nearest player's x-position
+#[allow(clippy::type_complexity)]
+fn update_tracking_enemies(
+    game: Res<GameResources>,
+    settings: Res<Settings>,
+    players: Query<&Transform, Without<Enemy>>,
+    mut trackers: Query<
+        (&Tracking, &mut Velocity, &Transform),
+        (With<Enemy>, Without<MoveToTarget>),
+    >,
+) {
+    for (tracking, mut enemy_vel, transform) in trackers.iter_mut() {
+        let Some(player_position) = game.nearest_player(transform.translation, &players) else {
+            continue;
+        };
+        let offset = player_position.x - transform.translation.x;
+        let max_velocity = tracking.max_velocity * settings.game_speed.0;
+        enemy_vel.linvel.x = f32::clamp(offset * ENEMY_TRACKING_GAIN, -max_velocity, max_velocity);
+    }
+}
+
+// Flies enemies along their entry curve, which ends right at their `MoveToTarget` spot
+fn follow_entry_paths(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut enemies: Query<(Entity, &mut EntryPath, &mut Velocity, &mut Transform), With<Enemy>>,
+) {
+    let delta_time = capped_delta_seconds(&time) * settings.game_speed.0;
+    for (enemy_entity, mut path, mut enemy_vel, mut transform) in enemies.iter_mut() {
+        enemy_vel.linvel = Vec3::ZERO;
+        if path.delay > 0.0 {
+            path.delay -= delta_time;
+            continue;
+        }
 
-            enemy.shot_cooldown_timer =
-                rng.gen_range(ENEMY_COOLDOWN_RANGE_S.0..=ENEMY_COOLDOWN_RANGE_S.1);
+        path.progress = f32::min(path.progress + delta_time / ENTRY_PATH_DURATION_S, 1.0);
+        // Eased in and out, so the ships slow down as they settle into the formation
+        let t = path.progress * path.progress * (3.0 - 2.0 * path.progress);
+        transform.translation = path.point(t);
+        if path.progress >= 1.0 {
+            commands.entity(enemy_entity).remove::<EntryPath>();
         }
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn update_move_to_target(
     mut commands: Commands,
-    mut enemies: Query<(Entity, &MoveToTarget, &mut Velocity, &mut Transform), With<Enemy>>,
+    mut enemies: Query<
+        (Entity, &MoveToTarget, &mut Velocity, &mut Transform),
+        (With<Enemy>, Without<EntryPath>),
+    >,
 ) {
     for (enemy_entity, target, mut enemy_vel, mut transform) in enemies.iter_mut() {
         if target.target.distance(transform.translation) > 0.10 {
@@ -270,35 +756,121 @@ fn update_move_to_target(
     }
 }
 
+fn update_spawn_fade(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut fading: Query<(Entity, &mut SpawnFade)>,
+    children: Query<&Children>,
+    mut scene_materials: Query<(&mut Handle<StandardMaterial>, Option<&FadeMaterial>)>,
+) {
+    for (enemy_entity, mut fade) in fading.iter_mut() {
+        fade.timer -= time.delta_seconds();
+        let done = fade.timer <= 0.0;
+        let progress = 1.0 - f32::max(fade.timer, 0.0) / ENEMY_SPAWN_FADE_DURATION_S;
+
+        for descendant in children.iter_descendants(enemy_entity) {
+            let Ok((mut handle, fade_material)) = scene_materials.get_mut(descendant) else {
+                continue;
+            };
+
+            let (original_alpha, original_alpha_mode) = match fade_material {
+                Some(fade_material) => (
+                    fade_material.original_alpha,
+                    fade_material.original_alpha_mode,
+                ),
+                None => {
+                    // The scene may only have been instantiated this frame
+                    let Some(material) = materials.get(&handle) else {
+                        continue;
+                    };
+                    let material = material.clone();
+                    let original = (material.base_color.a(), material.alpha_mode);
+                    *handle = materials.add(material);
+                    commands.entity(descendant).insert(FadeMaterial {
+                        original_alpha: original.0,
+                        original_alpha_mode: original.1,
+                    });
+                    original
+                }
+            };
+
+            let Some(material) = materials.get_mut(&handle) else {
+                continue;
+            };
+            if done {
+                material.base_color.set_a(original_alpha);
+                material.alpha_mode = original_alpha_mode;
+            } else {
+                material.base_color.set_a(original_alpha * progress);
+                material.alpha_mode = AlphaMode::Blend;
+            }
+        }
+
+        if done {
+            commands.entity(enemy_entity).remove::<SpawnFade>();
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn change_wave(
     commands: Commands,
     mut ev: EventWriter<NewWaveEvent>,
     asset_server: Res<AssetServer>,
     mut ai_state: ResMut<EnemyAIState>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut run_ended: EventWriter<RunEndedEvent>,
+    mut achievements: ResMut<Achievements>,
+    game: Res<GameResources>,
+    bounds: Res<PlayfieldBounds>,
+    tutorial: Res<Tutorial>,
+    mut reward_offer: ResMut<RewardOffer>,
+    mut rng: ResMut<GameRng>,
     enemies: Query<With<Enemy>>,
 ) {
-    if !enemies.is_empty() {
+    // While a reward is on offer, the next wave is spawned once the player has picked one
+    if !enemies.is_empty() || tutorial.is_active() || reward_offer.active {
         return;
     }
 
+    achievements.record_wave_cleared();
+
     let waves = get_waves();
     ai_state.current_wave += 1;
+    rng.start_wave(ai_state.current_wave);
     ev.send(NewWaveEvent {
         wave: ai_state.current_wave,
     });
     if ai_state.current_wave >= waves.len() as u32 {
-        println!("Done with all waves!");
+        info!("Done with all waves!");
+        achievements.record_run_completed();
+        run_ended.send(RunEndedEvent {
+            score: game.score,
+            wave: ai_state.current_wave,
+        });
         next_state.set(GameState::Menu);
         return;
     }
 
-    spawn_wave(ai_state.current_wave as usize, commands, asset_server);
+    if rng.waves().gen_bool(REWARD_CHANCE) {
+        reward_offer.active = true;
+        return;
+    }
+
+    spawn_wave(
+        ai_state.current_wave as usize,
+        &bounds,
+        &mut rng,
+        commands,
+        asset_server,
+    );
 }
 
 fn update_ui(
     mut er: EventReader<NewWaveEvent>,
     asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
     mut ui: Query<&mut Text, With<WaveUI>>,
 ) {
     for event in er.iter() {
@@ -308,7 +880,7 @@ fn update_ui(
                 TextStyle {
                     font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                     font_size: 45.0,
-                    color: Color::WHITE,
+                    color: theme.text,
                 },
             );
         }
@@ -323,6 +895,47 @@ impl EnemyType {
             EnemyType::Type3 => "Spaceship3/model.obj".to_string(),
         }
     }
+
+    // Half-extents roughly match the ship models after they are scaled down by 0.001
+    fn get_collider(&self, scale: f32) -> Collider {
+        let half_extents = match self {
+            EnemyType::Type1 => Vec3::new(0.32, 0.12, 0.35),
+            EnemyType::Type2 => Vec3::new(0.16, 0.12, 0.2),
+            EnemyType::Type3 => Vec3::new(0.22, 0.12, 0.3),
+        } * scale;
+        Collider::cuboid(half_extents.x, half_extents.y, half_extents.z)
+    }
+
+    // Chance to actually fire whenever the shot cooldown runs out
+    pub fn fire_probability(&self) -> f32 {
+        match self {
+            EnemyType::Type1 => 0.5,
+            EnemyType::Type2 => 0.4,
+            EnemyType::Type3 => 0.75,
+        }
+    }
+
+    // Min and max time in seconds between attempts to fire
+    pub fn cooldown_range(&self) -> (f32, f32) {
+        match self {
+            EnemyType::Type1 => (2.0, 3.0),
+            EnemyType::Type2 => (2.5, 3.5),
+            EnemyType::Type3 => (1.2, 2.0),
+        }
+    }
+
+    fn roll_cooldown(&self, rng: &mut impl Rng) -> f32 {
+        let (min, max) = self.cooldown_range();
+        rng.gen_range(min..=max)
+    }
+}
+
+impl Default for EnemyFireLimits {
+    fn default() -> Self {
+        Self {
+            max_live_bullets: MAX_LIVE_ENEMY_BULLETS,
+        }
+    }
 }
 
 impl Default for EnemyAIState {
@@ -335,30 +948,323 @@ impl Default for EnemyAIState {
     }
 }
 
+// Shapes that enemy positions can be laid out in, in the same grid units as `EnemyInstance`
+#[derive(Clone, Copy, Debug)]
+pub enum Formation {
+    // A rectangular block of evenly spaced columns and rows
+    Grid {
+        columns: u32,
+        rows: u32,
+    },
+    // Two diagonal arms meeting at a tip that points at the player
+    V {
+        arm_length: u32,
+    },
+    // The outline of a diamond, `radius` rows from the center to each corner
+    Diamond {
+        radius: u32,
+    },
+    // Ships spread evenly along a circular arc that bulges towards the player
+    Arc {
+        count: u32,
+        radius: f32,
+        spread_degrees: f32,
+    },
+}
+
+impl Formation {
+    // Positions centered around (0, 0), columns are two units apart like in the grid waves
+    pub fn positions(&self) -> Vec<[f32; 2]> {
+        match *self {
+            Formation::Grid { columns, rows } => {
+                let mut positions = Vec::new();
+                for col in 0..columns {
+                    for row in 0..rows {
+                        positions.push([
+                            (2 * col) as f32 - (columns as f32 - 1.0),
+                            row as f32 - (rows.saturating_sub(1) / 2) as f32,
+                        ]);
+                    }
+                }
+                positions
+            }
+            Formation::V { arm_length } => {
+                let tip_row = (arm_length as f32 / 2.0).ceil();
+                let mut positions = vec![[0.0, tip_row]];
+                for i in 1..=arm_length {
+                    let row = tip_row - i as f32;
+                    positions.push([-2.0 * i as f32, row]);
+                    positions.push([2.0 * i as f32, row]);
+                }
+                positions
+            }
+            Formation::Diamond { radius } => {
+                let radius = radius as i32;
+                let mut positions = Vec::new();
+                for row in -radius..=radius {
+                    let col = 2 * (radius - row.abs());
+                    positions.push([-col as f32, row as f32]);
+                    if col != 0 {
+                        positions.push([col as f32, row as f32]);
+                    }
+                }
+                positions
+            }
+            Formation::Arc {
+                count,
+                radius,
+                spread_degrees,
+            } => {
+                if count <= 1 {
+                    return vec![[0.0, 0.0]];
+                }
+                let step = spread_degrees / (count - 1) as f32;
+                (0..count)
+                    .map(|i| {
+                        let angle = (-spread_degrees / 2.0 + i as f32 * step).to_radians();
+                        // Columns are half as wide as rows, so stretch x to keep the arc round
+                        [2.0 * radius * angle.sin(), radius * (angle.cos() - 1.0)]
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl Wave {
+    fn new(enemies: Vec<EnemyInstance>) -> Wave {
+        Wave {
+            enemies,
+            health_multiplier: 1.0,
+            entry: EntryStyle::Scatter,
+        }
+    }
+
+    // Places one enemy at every position of the formation, `enemy` decides what goes where
+    fn from_formation(formation: Formation, enemy: impl Fn([f32; 2]) -> EnemyInstance) -> Wave {
+        Wave::new(formation.positions().into_iter().map(enemy).collect())
+    }
+
+    fn with_health_multiplier(mut self, health_multiplier: f32) -> Wave {
+        self.health_multiplier = health_multiplier;
+        self
+    }
+
+    fn with_entry(mut self, entry: EntryStyle) -> Wave {
+        self.entry = entry;
+        self
+    }
+
+    // Rounded to the nearest whole hit point, but never down to zero
+    fn enemy_health(&self, enemy: &EnemyInstance) -> u32 {
+        ((enemy.health as f32 * self.health_multiplier).round() as u32).max(1)
+    }
+}
+
 // TODO: Specify this in e.g. a JSON file later?
 fn get_waves() -> Vec<Wave> {
-    let mut enemies0 = Vec::new();
-    for col in (-4..=4).step_by(2) {
-        for row in -1..=1 {
-            enemies0.push(EnemyInstance {
-                position: [col, row],
-                ship_type: EnemyType::Type1,
-                health: 2,
-            });
+    let grid0 = Wave::from_formation(
+        Formation::Grid {
+            columns: 5,
+            rows: 3,
+        },
+        |position| EnemyInstance {
+            position,
+            ship_type: EnemyType::Type1,
+            health: 2,
+            tracking: position == [0.0, 1.0],
+            boss: false,
+            bullet_bounces: 0,
+            shielded: false,
+            lays_mines: false,
+            support: false,
+        },
+    );
+
+    let v = Wave::from_formation(Formation::V { arm_length: 4 }, |position| {
+        EnemyInstance {
+            position,
+            ship_type: EnemyType::Type1,
+            health: 2,
+            // The tip of the V follows the player and the ends of the arms lay mines
+            tracking: position[0] == 0.0,
+            boss: false,
+            bullet_bounces: 0,
+            shielded: false,
+            lays_mines: position[0].abs() == 8.0,
+            support: false,
+        }
+    });
+
+    let grid1 = Wave::from_formation(
+        Formation::Grid {
+            columns: 6,
+            rows: 5,
+        },
+        |position| EnemyInstance {
+            position,
+            ship_type: EnemyType::Type2,
+            health: 2,
+            tracking: position == [-1.0, 2.0],
+            boss: false,
+            // The back row fires bouncing shots
+            bullet_bounces: if position[1] == -2.0 { 2 } else { 0 },
+            shielded: false,
+            lays_mines: false,
+            // Two supports in the middle keep the rest of the grid patched up
+            support: position[1] == -1.0 && position[0].abs() == 1.0,
+        },
+    );
+
+    let diamond = Wave::from_formation(Formation::Diamond { radius: 2 }, |position| {
+        EnemyInstance {
+            position,
+            ship_type: EnemyType::Type2,
+            health: 3,
+            tracking: false,
+            boss: false,
+            // The side corners fire bouncing shots and the front and back corners are shielded
+            bullet_bounces: if position[1] == 0.0 { 1 } else { 0 },
+            shielded: position[0] == 0.0,
+            lays_mines: false,
+            support: false,
         }
+    });
+
+    let arc = Wave::from_formation(
+        Formation::Arc {
+            count: 7,
+            radius: 3.0,
+            spread_degrees: 120.0,
+        },
+        |position| EnemyInstance {
+            position,
+            ship_type: EnemyType::Type3,
+            health: 2,
+            tracking: false,
+            boss: false,
+            bullet_bounces: 0,
+            shielded: false,
+            lays_mines: false,
+            support: false,
+        },
+    );
+
+    let boss = vec![EnemyInstance {
+        position: [0.0, -1.0],
+        ship_type: EnemyType::Type3,
+        health: 60,
+        tracking: false,
+        boss: true,
+        bullet_bounces: 0,
+        shielded: false,
+        lays_mines: false,
+        support: false,
+    }];
+
+    let waves: Vec<Wave> = vec![
+        grid0,
+        v.with_entry(EntryStyle::Swoop {
+            from: EntrySide::Nearest,
+        }),
+        grid1.with_health_multiplier(1.25),
+        diamond.with_health_multiplier(1.5),
+        arc.with_health_multiplier(1.5)
+            .with_entry(EntryStyle::Swoop {
+                from: EntrySide::Left,
+            }),
+        Wave::new(boss),
+    ];
+    waves
+}
+
+// The index of the boss wave, which the practice boss option starts at
+pub fn boss_wave_index() -> u32 {
+    get_waves()
+        .iter()
+        .position(|wave| wave.enemies.iter().any(|enemy| enemy.boss))
+        .unwrap_or(0) as u32
+}
+
+// A single slow enemy to practice on, see the tutorial plugin
+fn get_tutorial_wave() -> Wave {
+    Wave::new(vec![EnemyInstance {
+        position: [0.0, 0.0],
+        ship_type: EnemyType::Type2,
+        health: 3,
+        tracking: false,
+        boss: false,
+        bullet_bounces: 0,
+        shielded: false,
+        lays_mines: false,
+        support: false,
+    }])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid_wave(columns: u32) -> Wave {
+        Wave::from_formation(Formation::Grid { columns, rows: 2 }, |position| {
+            EnemyInstance {
+                position,
+                ship_type: EnemyType::Type1,
+                health: 1,
+                tracking: false,
+                boss: false,
+                bullet_bounces: 0,
+                shielded: false,
+                lays_mines: false,
+                support: false,
+            }
+        })
     }
 
-    let mut enemies1 = Vec::new();
-    for col in (-5..=5).step_by(2) {
-        for row in -2..=2 {
-            enemies1.push(EnemyInstance {
-                position: [col, row],
-                ship_type: EnemyType::Type2,
-                health: 2,
-            });
+    fn spacing(wave: &Wave, half_width: f32) -> f32 {
+        formation_x_spacing(wave, &PlayfieldBounds { half_width })
+    }
+
+    // Where the outermost ship ends up, measured from the center of the playfield
+    fn outermost_x(wave: &Wave, half_width: f32) -> f32 {
+        let widest_column = wave
+            .enemies
+            .iter()
+            .map(|enemy| enemy.position[0].abs())
+            .fold(0.0, f32::max);
+        widest_column * spacing(wave, half_width)
+    }
+
+    #[test]
+    fn wide_bounds_keep_the_full_spacing() {
+        assert_eq!(spacing(&grid_wave(6), 10.0), MAX_FORMATION_X_SPACING);
+    }
+
+    #[test]
+    fn standard_bounds_fit_every_wave() {
+        let standard = PlayfieldBounds::default().half_width;
+        // Six columns reach 2.5 units out and fit as they are
+        assert_eq!(spacing(&grid_wave(6), standard), MAX_FORMATION_X_SPACING);
+        for wave in get_waves() {
+            assert!(outermost_x(&wave, standard) <= standard - FORMATION_EDGE_MARGIN + 1e-4);
         }
     }
 
-    let waves: Vec<Wave> = vec![Wave { enemies: enemies0 }, Wave { enemies: enemies1 }];
-    waves
+    #[test]
+    fn narrow_bounds_compress_the_formation() {
+        let wave = grid_wave(6);
+        let compressed = spacing(&wave, 3.0);
+        assert!(compressed < MAX_FORMATION_X_SPACING);
+        assert!((outermost_x(&wave, 3.0) - (3.0 - FORMATION_EDGE_MARGIN)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn bounds_narrower_than_the_margin_stack_the_columns() {
+        assert_eq!(spacing(&grid_wave(6), 1.0), 0.0);
+    }
+
+    #[test]
+    fn single_column_keeps_the_full_spacing() {
+        assert_eq!(spacing(&grid_wave(1), 1.0), MAX_FORMATION_X_SPACING);
+    }
 }
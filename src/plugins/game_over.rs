@@ -0,0 +1,194 @@
+use bevy::{
+    prelude::{
+        default, in_state, App, AssetServer, BuildChildren, Button, Changed, Commands, Component,
+        DespawnRecursiveExt, Entity, EventWriter, IntoSystemConfigs, NextState, NodeBundle,
+        OnEnter, OnExit, Plugin, Query, Res, ResMut, Resource, TextBundle, Update, With,
+    },
+    text::TextStyle,
+    ui::{
+        AlignItems, BackgroundColor, FlexDirection, Interaction, JustifyContent, Style, UiRect, Val,
+    },
+};
+
+use crate::{
+    plugins::{
+        achievements::Achievements,
+        enemy_wave_plugin::{boss_wave_index, reset_ai_state, EnemyAIState},
+        leaderboard::RunEndedEvent,
+        main_menu::spawn_button,
+    },
+    state::GameState,
+    theme::{Theme, ThemedUi},
+    GameResources,
+};
+
+// Score spent to continue from the wave the player died on
+const CONTINUE_COST: u32 = 500;
+
+// The wave the player died on, kept around since the AI state is reset when leaving the game
+#[derive(Resource, Default)]
+struct ContinueOffer {
+    wave: u32,
+}
+
+#[derive(Component)]
+struct GameOverUiRoot {}
+
+#[derive(Component, Clone, Copy)]
+enum GameOverButton {
+    Continue,
+    Restart,
+    Menu,
+}
+
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ContinueOffer>()
+            .add_systems(
+                OnExit(GameState::Game),
+                remember_wave.before(reset_ai_state),
+            )
+            .add_systems(OnEnter(GameState::GameOver), init_ui)
+            .add_systems(OnExit(GameState::GameOver), destroy_ui)
+            .add_systems(Update, update_buttons.run_if(in_state(GameState::GameOver)));
+    }
+}
+
+fn remember_wave(ai_state: Res<EnemyAIState>, mut offer: ResMut<ContinueOffer>) {
+    offer.wave = ai_state.current_wave;
+}
+
+fn init_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+    game: Res<GameResources>,
+    achievements: Res<Achievements>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 45.0,
+        color: theme.text,
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section("Game over", text_style.clone()))
+                .insert(ThemedUi {});
+            parent
+                .spawn(
+                    TextBundle::from_section(format!("Score: {}", game.score), text_style.clone())
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(10.0)),
+                            ..default()
+                        }),
+                )
+                .insert(ThemedUi {});
+
+            for achievement in achievements.unlocked_this_run.iter() {
+                parent
+                    .spawn(TextBundle::from_section(
+                        format!("Unlocked: {}", achievement.name()),
+                        TextStyle {
+                            font_size: 30.0,
+                            ..text_style.clone()
+                        },
+                    ))
+                    .insert(ThemedUi {});
+            }
+
+            if game.score >= CONTINUE_COST {
+                spawn_button(
+                    parent,
+                    &asset_server,
+                    &theme,
+                    &format!("Continue (cost {})", CONTINUE_COST),
+                    GameOverButton::Continue,
+                );
+            }
+            spawn_button(
+                parent,
+                &asset_server,
+                &theme,
+                "Restart",
+                GameOverButton::Restart,
+            );
+            spawn_button(parent, &asset_server, &theme, "Menu", GameOverButton::Menu);
+        })
+        .insert(GameOverUiRoot {});
+}
+
+fn destroy_ui(mut commands: Commands, root_query: Query<Entity, With<GameOverUiRoot>>) {
+    for ui in root_query.iter() {
+        commands.entity(ui).despawn_recursive();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_buttons(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &GameOverButton),
+        (Changed<Interaction>, With<Button>),
+    >,
+    theme: Res<Theme>,
+    mut game: ResMut<GameResources>,
+    mut ai_state: ResMut<EnemyAIState>,
+    offer: Res<ContinueOffer>,
+    mut run_ended: EventWriter<RunEndedEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, mut color, button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = theme.button_pressed.into();
+                match button {
+                    GameOverButton::Continue => {
+                        if game.score < CONTINUE_COST {
+                            continue;
+                        }
+                        // Keep the remaining score, a fresh player is spawned when re-entering
+                        // the game at full health
+                        game.score -= CONTINUE_COST;
+                        game.continuing = true;
+                        ai_state.current_wave = offer.wave;
+                        next_state.set(GameState::Game);
+                    }
+                    GameOverButton::Restart => {
+                        run_ended.send(RunEndedEvent {
+                            score: game.score,
+                            wave: offer.wave,
+                        });
+                        // A practice run restarts at the boss again
+                        if game.practice_boss {
+                            ai_state.current_wave = boss_wave_index();
+                        }
+                        next_state.set(GameState::Game);
+                    }
+                    GameOverButton::Menu => {
+                        run_ended.send(RunEndedEvent {
+                            score: game.score,
+                            wave: offer.wave,
+                        });
+                        next_state.set(GameState::Menu);
+                    }
+                }
+            }
+            _ => {
+                *color = theme.button.into();
+            }
+        }
+    }
+}
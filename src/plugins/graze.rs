@@ -0,0 +1,155 @@
+use bevy::{
+    prelude::{
+        default, in_state, Added, App, AssetServer, BuildChildren, Commands, Component,
+        DespawnRecursiveExt, DetectChanges, Entity, IntoSystemConfigs, OnEnter, OnExit, Plugin,
+        Query, Res, ResMut, Resource, TextBundle, Transform, TransformBundle, Update, With,
+        Without,
+    },
+    text::{Text, TextStyle},
+    ui::{PositionType, Style, Val},
+};
+use bevy_rapier3d::prelude::{Collider, RapierContext, Sensor};
+
+use crate::{
+    combat::{Bullet, ParticleHitEffect},
+    state::GameState,
+    theme::Theme,
+    GameResources, Player,
+};
+
+// Enemy bullets that pass within this distance of the ship's center count as a graze
+const GRAZE_RADIUS: f32 = 0.6;
+const GRAZE_SCORE: u32 = 10;
+
+// Grazes in the current run
+#[derive(Resource, Default)]
+struct GrazeCounter {
+    count: u32,
+}
+
+// A sensor around the ship, larger than its hitbox, that picks up near-misses
+#[derive(Component)]
+struct GrazeSensor {}
+
+// The bullet is inside the graze sensor, it counts once it leaves without hitting the ship
+#[derive(Component)]
+struct Grazing {}
+
+// Already counted, so a bullet can't be grazed twice
+#[derive(Component)]
+struct Grazed {}
+
+#[derive(Component)]
+struct GrazeHud {}
+
+pub struct GrazePlugin;
+
+impl Plugin for GrazePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GrazeCounter>()
+            .add_systems(OnEnter(GameState::Game), (reset_graze_counter, init_hud))
+            .add_systems(OnExit(GameState::Game), destroy_hud)
+            .add_systems(
+                Update,
+                (attach_graze_sensors, detect_grazes, update_hud).run_if(in_state(GameState::Game)),
+            );
+    }
+}
+
+fn reset_graze_counter(mut counter: ResMut<GrazeCounter>) {
+    *counter = GrazeCounter::default();
+}
+
+fn attach_graze_sensors(mut commands: Commands, players: Query<Entity, Added<Player>>) {
+    for player in players.iter() {
+        commands.entity(player).with_children(|children| {
+            children
+                .spawn(TransformBundle::default())
+                .insert(Collider::ball(GRAZE_RADIUS))
+                .insert(Sensor {})
+                .insert(GrazeSensor {});
+        });
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn detect_grazes(
+    mut commands: Commands,
+    rapier_context: Res<RapierContext>,
+    mut game: ResMut<GameResources>,
+    mut counter: ResMut<GrazeCounter>,
+    sensors: Query<Entity, With<GrazeSensor>>,
+    entering: Query<(Entity, &Bullet), (Without<Grazing>, Without<Grazed>)>,
+    leaving: Query<(Entity, &Transform), With<Grazing>>,
+) {
+    // In co-op each ship has its own sensor, a bullet slipping between both still counts once
+    let in_sensor = |bullet_entity: Entity| {
+        sensors
+            .iter()
+            .any(|sensor| rapier_context.intersection_pair(sensor, bullet_entity) == Some(true))
+    };
+
+    for (bullet_entity, bullet) in entering.iter() {
+        if !bullet.is_player_bullet && in_sensor(bullet_entity) {
+            commands.entity(bullet_entity).insert(Grazing {});
+        }
+    }
+
+    // Bullets that hit the ship are despawned before they get out of the sensor again
+    for (bullet_entity, transform) in leaving.iter() {
+        if in_sensor(bullet_entity) {
+            continue;
+        }
+        commands
+            .entity(bullet_entity)
+            .remove::<Grazing>()
+            .insert(Grazed {});
+        counter.count += 1;
+        game.score += GRAZE_SCORE;
+        commands.spawn(ParticleHitEffect {
+            position: transform.translation,
+            is_large: false,
+        });
+    }
+}
+
+fn init_hud(mut commands: Commands) {
+    commands
+        .spawn(TextBundle::default().with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        }))
+        .insert(GrazeHud {});
+}
+
+fn destroy_hud(mut commands: Commands, huds: Query<Entity, With<GrazeHud>>) {
+    for hud in huds.iter() {
+        commands.entity(hud).despawn_recursive();
+    }
+}
+
+// Only rebuilt when there is something new to show, not every frame
+fn update_hud(
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+    counter: Res<GrazeCounter>,
+    new_huds: Query<(), Added<GrazeHud>>,
+    mut huds: Query<&mut Text, With<GrazeHud>>,
+) {
+    if !counter.is_changed() && !theme.is_changed() && new_huds.is_empty() {
+        return;
+    }
+
+    for mut text in huds.iter_mut() {
+        *text = Text::from_section(
+            format!("Grazes: {}", counter.count),
+            TextStyle {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 25.0,
+                color: theme.text,
+            },
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use bevy::{
+    prelude::{
+        default, in_state, App, BuildChildren, Camera, Color, Commands, Component,
+        DespawnRecursiveExt, Entity, GlobalTransform, IntoSystemConfigs, NodeBundle, OnExit,
+        Parent, Plugin, Query, Res, Transform, Update, Vec3, Visibility, With, Without,
+    },
+    ui::{BackgroundColor, PositionType, Style, UiScale, Val},
+};
+
+use crate::{camera::world_to_ui_position, combat::Damageable, enemy::Enemy, state::GameState};
+
+const HEALTH_BAR_WIDTH_PX: f32 = 40.0;
+const HEALTH_BAR_HEIGHT_PX: f32 = 5.0;
+// Bars are placed slightly up the screen (towards -z) from the enemy's center
+const HEALTH_BAR_OFFSET: Vec3 = Vec3::new(0.0, 0.0, -0.4);
+
+#[derive(Component)]
+struct HealthBar {
+    owner: Entity,
+}
+
+#[derive(Component)]
+struct HealthBarFill {}
+
+pub struct HealthBarPlugin;
+
+impl Plugin for HealthBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (spawn_health_bars, update_health_bars)
+                .chain()
+                .run_if(in_state(GameState::Game)),
+        )
+        .add_systems(OnExit(GameState::Game), destroy_health_bars);
+    }
+}
+
+// Only enemies that can take more than one hit and have already been hit get a bar
+fn spawn_health_bars(
+    mut commands: Commands,
+    enemies: Query<(Entity, &Damageable), With<Enemy>>,
+    bars: Query<&HealthBar>,
+) {
+    for (entity, damageable) in enemies.iter() {
+        if damageable.max_health <= 1 || damageable.health >= damageable.max_health {
+            continue;
+        }
+        if bars.iter().any(|bar| bar.owner == entity) {
+            continue;
+        }
+
+        commands
+            .spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(HEALTH_BAR_WIDTH_PX),
+                    height: Val::Px(HEALTH_BAR_HEIGHT_PX),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgba(0.2, 0.0, 0.0, 0.8)),
+                // Hidden until it has been positioned over the enemy
+                visibility: Visibility::Hidden,
+                ..default()
+            })
+            .with_children(|parent| {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(Color::rgb(0.9, 0.1, 0.1)),
+                        ..default()
+                    })
+                    .insert(HealthBarFill {});
+            })
+            .insert(HealthBar { owner: entity });
+    }
+}
+
+fn update_health_bars(
+    mut commands: Commands,
+    ui_scale: Res<UiScale>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    enemies: Query<(&Damageable, &Transform), With<Enemy>>,
+    mut bars: Query<(Entity, &HealthBar, &mut Style, &mut Visibility), Without<HealthBarFill>>,
+    mut fills: Query<(&Parent, &mut Style), With<HealthBarFill>>,
+) {
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+
+    for (bar_entity, bar, mut style, mut visibility) in bars.iter_mut() {
+        // The bar goes away together with its enemy
+        let Ok((damageable, transform)) = enemies.get(bar.owner) else {
+            commands.entity(bar_entity).despawn_recursive();
+            continue;
+        };
+
+        let position = transform.translation + HEALTH_BAR_OFFSET;
+        match world_to_ui_position(camera, camera_transform, &ui_scale, position) {
+            Some(screen_position) => {
+                style.left = Val::Px(screen_position.x - HEALTH_BAR_WIDTH_PX / 2.0);
+                style.top = Val::Px(screen_position.y - HEALTH_BAR_HEIGHT_PX / 2.0);
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+
+        let fraction = damageable.health as f32 / damageable.max_health.max(1) as f32;
+        for (parent, mut fill_style) in fills.iter_mut() {
+            if parent.get() == bar_entity {
+                fill_style.width = Val::Percent(fraction * 100.0);
+            }
+        }
+    }
+}
+
+fn destroy_health_bars(mut commands: Commands, bars: Query<Entity, With<HealthBar>>) {
+    for bar in bars.iter() {
+        commands.entity(bar).despawn_recursive();
+    }
+}
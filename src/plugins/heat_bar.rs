@@ -0,0 +1,164 @@
+use bevy::{
+    prelude::{
+        default, in_state, App, AssetServer, BuildChildren, Color, Commands, Component,
+        DespawnRecursiveExt, Entity, IntoSystemConfigs, NodeBundle, OnEnter, OnExit, Plugin, Query,
+        Res, TextBundle, Update, Visibility, With,
+    },
+    text::TextStyle,
+    ui::{AlignItems, BackgroundColor, FlexDirection, PositionType, Style, UiRect, Val},
+};
+
+use crate::{
+    controls::PlayerSlot,
+    settings::{Settings, WeaponModel},
+    state::GameState,
+    GameResources, Player,
+};
+
+const HEAT_BAR_WIDTH_PX: f32 = 200.0;
+const HEAT_BAR_HEIGHT_PX: f32 = 10.0;
+const HEAT_COLOR: Color = Color::rgb(1.0, 0.6, 0.1);
+const OVERHEATED_COLOR: Color = Color::rgb(0.9, 0.1, 0.1);
+
+#[derive(Component)]
+struct HeatBarRoot {}
+
+// Tagged with the ship they show, co-op has a bar for each pilot
+#[derive(Component)]
+struct HeatBarFill {
+    slot: PlayerSlot,
+}
+
+#[derive(Component)]
+struct OverheatedLabel {
+    slot: PlayerSlot,
+}
+
+pub struct HeatBarPlugin;
+
+impl Plugin for HeatBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Game), init_heat_bar)
+            .add_systems(OnExit(GameState::Game), destroy_heat_bar)
+            .add_systems(Update, update_heat_bar.run_if(in_state(GameState::Game)));
+    }
+}
+
+// Only shown with the heat weapon model, the cooldown model has no heat to show
+fn init_heat_bar(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    game: Res<GameResources>,
+) {
+    if settings.weapon_model != WeaponModel::Heat {
+        return;
+    }
+
+    // Centered for a single ship, or under each half of the screen in co-op
+    let bars: &[(PlayerSlot, f32)] = if game.two_players {
+        &[(PlayerSlot::One, 25.0), (PlayerSlot::Two, 75.0)]
+    } else {
+        &[(PlayerSlot::One, 50.0)]
+    };
+    for (slot, center) in bars {
+        spawn_heat_bar(&mut commands, &asset_server, *slot, *center);
+    }
+}
+
+fn spawn_heat_bar(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    slot: PlayerSlot,
+    center: f32,
+) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                left: Val::Percent(center),
+                margin: UiRect::left(Val::Px(-HEAT_BAR_WIDTH_PX / 2.0)),
+                width: Val::Px(HEAT_BAR_WIDTH_PX),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle {
+                    visibility: Visibility::Hidden,
+                    ..TextBundle::from_section(
+                        "OVERHEATED",
+                        TextStyle {
+                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                            font_size: 20.0,
+                            color: OVERHEATED_COLOR,
+                        },
+                    )
+                })
+                .insert(OverheatedLabel { slot });
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Px(HEAT_BAR_HEIGHT_PX),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::rgba(0.1, 0.1, 0.1, 0.8)),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: BackgroundColor(HEAT_COLOR),
+                            ..default()
+                        })
+                        .insert(HeatBarFill { slot });
+                });
+        })
+        .insert(HeatBarRoot {});
+}
+
+fn destroy_heat_bar(mut commands: Commands, roots: Query<Entity, With<HeatBarRoot>>) {
+    for root in roots.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+}
+
+// The bar turns red while the gun is overheated and locked
+fn update_heat_bar(
+    players: Query<&Player>,
+    mut fills: Query<(&HeatBarFill, &mut Style, &mut BackgroundColor)>,
+    mut labels: Query<(&OverheatedLabel, &mut Visibility)>,
+) {
+    // A destroyed ship's bar is left as it was
+    for (fill, mut style, mut color) in fills.iter_mut() {
+        let Some(player) = players.iter().find(|player| player.slot == fill.slot) else {
+            continue;
+        };
+        style.width = Val::Percent(player.heat * 100.0);
+        *color = BackgroundColor(if player.overheated {
+            OVERHEATED_COLOR
+        } else {
+            HEAT_COLOR
+        });
+    }
+    for (label, mut visibility) in labels.iter_mut() {
+        let Some(player) = players.iter().find(|player| player.slot == label.slot) else {
+            continue;
+        };
+        *visibility = if player.overheated {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
@@ -0,0 +1,122 @@
+use bevy::{
+    prelude::{
+        default, in_state, App, AssetServer, BuildChildren, Color, Commands, Component,
+        DespawnRecursiveExt, Entity, Input, IntoSystemConfigs, KeyCode, NodeBundle, OnExit, Plugin,
+        Query, Res, ResMut, TextBundle, Update, With,
+    },
+    text::TextStyle,
+    time::Time,
+    ui::{AlignItems, FlexDirection, JustifyContent, PositionType, Style, UiRect, Val},
+};
+use bevy_rapier3d::prelude::RapierConfiguration;
+
+use crate::{
+    controls::KeyBindings,
+    settings::Settings,
+    state::GameState,
+    theme::{Theme, ThemedUi},
+};
+
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.7);
+
+#[derive(Component)]
+struct HelpOverlayRoot {}
+
+pub struct HelpOverlayPlugin;
+
+impl Plugin for HelpOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnExit(GameState::Game), destroy_overlay)
+            .add_systems(Update, toggle_overlay.run_if(in_state(GameState::Game)));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn toggle_overlay(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    theme: Res<Theme>,
+    settings: Res<Settings>,
+    mut time: ResMut<Time>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+    overlays: Query<Entity, With<HelpOverlayRoot>>,
+) {
+    if !input.just_pressed(bindings.toggle_help) {
+        return;
+    }
+
+    if !overlays.is_empty() {
+        for overlay in overlays.iter() {
+            commands.entity(overlay).despawn_recursive();
+        }
+        set_paused(&mut time, &mut rapier_config, false);
+        return;
+    }
+
+    if settings.pause_on_help {
+        set_paused(&mut time, &mut rapier_config, true);
+    }
+
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 30.0,
+        color: theme.text,
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            background_color: OVERLAY_COLOR.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            for (action, key) in bindings.describe() {
+                parent
+                    .spawn(
+                        TextBundle::from_section(
+                            format!("{}: {:?}", action, key),
+                            text_style.clone(),
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(5.0)),
+                            ..default()
+                        }),
+                    )
+                    .insert(ThemedUi {});
+            }
+        })
+        .insert(HelpOverlayRoot {});
+}
+
+fn destroy_overlay(
+    mut commands: Commands,
+    mut time: ResMut<Time>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+    root_query: Query<Entity, With<HelpOverlayRoot>>,
+) {
+    for overlay in root_query.iter() {
+        commands.entity(overlay).despawn_recursive();
+    }
+    set_paused(&mut time, &mut rapier_config, false);
+}
+
+// Stopping the clock freezes every timer and movement that is scaled by the frame time, the
+// physics pipeline is stopped separately
+fn set_paused(time: &mut Time, rapier_config: &mut RapierConfiguration, paused: bool) {
+    if paused {
+        time.pause();
+    } else {
+        time.unpause();
+    }
+    rapier_config.physics_pipeline_active = !paused;
+}
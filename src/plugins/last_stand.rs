@@ -0,0 +1,108 @@
+use bevy::{
+    prelude::{
+        in_state, shape, AlphaMode, App, Assets, BuildChildren, Color, Commands, Entity,
+        EventReader, IntoSystemConfigs, Mesh, OnExit, PbrBundle, Plugin, Query, Res, ResMut,
+        Resource, StandardMaterial, Update, With,
+    },
+    time::Time,
+};
+
+use crate::{
+    enemy::Enemy,
+    plugins::{boss::BossAttackState, enemy_wave_plugin::NewWaveEvent, tutorial::Tutorial},
+    state::GameState,
+};
+
+// A short slow-mo pulse when a wave is down to its last enemy, timed in real time
+const LAST_STAND_TIME_SCALE: f32 = 0.4;
+const LAST_STAND_DURATION_S: f32 = 0.6;
+const LAST_STAND_RING_RADIUS: f32 = 0.6;
+
+// Only fires once per wave, the wave change arms it again
+#[derive(Resource, Default)]
+struct LastStand {
+    triggered: bool,
+    slow_mo_timer: f32,
+}
+
+pub struct LastStandPlugin;
+
+impl Plugin for LastStandPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LastStand>()
+            .add_systems(OnExit(GameState::Game), end_last_stand)
+            .add_systems(
+                Update,
+                (rearm_last_stand, detect_last_stand, update_slow_mo)
+                    .chain()
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
+
+fn rearm_last_stand(mut er: EventReader<NewWaveEvent>, mut last_stand: ResMut<LastStand>) {
+    if er.iter().count() > 0 {
+        last_stand.triggered = false;
+    }
+}
+
+fn detect_last_stand(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut time: ResMut<Time>,
+    mut last_stand: ResMut<LastStand>,
+    tutorial: Res<Tutorial>,
+    enemies: Query<(Entity, Option<&BossAttackState>), With<Enemy>>,
+) {
+    if last_stand.triggered || tutorial.is_active() || enemies.iter().count() != 1 {
+        return;
+    }
+    let Ok((enemy, boss)) = enemies.get_single() else {
+        return;
+    };
+    // The boss is always alone, so there's nothing to emphasize
+    if boss.is_some() {
+        return;
+    }
+
+    last_stand.triggered = true;
+    last_stand.slow_mo_timer = LAST_STAND_DURATION_S;
+    time.set_relative_speed(LAST_STAND_TIME_SCALE);
+
+    // Ring the last enemy so it stands out, it is removed along with the enemy
+    let ring = commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Torus {
+                radius: LAST_STAND_RING_RADIUS,
+                ring_radius: 0.03,
+                ..Default::default()
+            })),
+            material: materials.add(StandardMaterial {
+                base_color: Color::rgba(1.0, 1.0, 1.0, 0.8),
+                emissive: Color::rgb_linear(6.0, 6.0, 6.0),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .id();
+    commands.entity(enemy).add_child(ring);
+}
+
+fn update_slow_mo(mut time: ResMut<Time>, mut last_stand: ResMut<LastStand>) {
+    if last_stand.slow_mo_timer <= 0.0 {
+        return;
+    }
+    // Counted in real time, otherwise the slow-mo would drag itself out
+    last_stand.slow_mo_timer -= time.raw_delta_seconds();
+    if last_stand.slow_mo_timer <= 0.0 {
+        time.set_relative_speed(1.0);
+    }
+}
+
+fn end_last_stand(mut time: ResMut<Time>, mut last_stand: ResMut<LastStand>) {
+    *last_stand = LastStand::default();
+    time.set_relative_speed(1.0);
+}
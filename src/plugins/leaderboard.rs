@@ -0,0 +1,312 @@
+use std::{
+    cmp::Reverse,
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+    log::{error, warn},
+    prelude::{
+        default, in_state, App, AssetServer, BuildChildren, Changed, Commands, Component,
+        DespawnRecursiveExt, Entity, Event, EventReader, Input, IntoSystemConfigs, KeyCode,
+        NextState, NodeBundle, OnEnter, OnExit, Plugin, Query, Res, ResMut, Resource, Startup,
+        TextBundle, Update, With,
+    },
+    text::TextStyle,
+    ui::{
+        AlignItems, BackgroundColor, FlexDirection, Interaction, JustifyContent, Style, UiRect, Val,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    plugins::main_menu::spawn_button,
+    rng::{GameRng, SECONDS_PER_DAY},
+    state::GameState,
+    theme::{Theme, ThemedUi},
+    GameResources,
+};
+
+const LEADERBOARD_PATH: &str = "leaderboard.json";
+const LEADERBOARD_SIZE: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LeaderboardEntry {
+    pub score: u32,
+    pub wave: u32,
+    // Seconds since the unix epoch
+    pub date: u64,
+}
+
+// Daily challenge runs are only compared against other runs on the same day
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DailyBest {
+    // Days since the unix epoch
+    pub day: u64,
+    pub score: u32,
+}
+
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+    #[serde(default)]
+    pub daily_best: Option<DailyBest>,
+}
+
+// Sent once a run is over for good, i.e. not when the player might still continue
+#[derive(Event)]
+pub struct RunEndedEvent {
+    pub score: u32,
+    pub wave: u32,
+}
+
+#[derive(Component)]
+struct LeaderboardUiRoot {}
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RunEndedEvent>()
+            .add_systems(Startup, load_leaderboard)
+            .add_systems(Update, record_run)
+            .add_systems(OnEnter(GameState::Leaderboard), init_ui)
+            .add_systems(OnExit(GameState::Leaderboard), destroy_ui)
+            .add_systems(
+                Update,
+                update_back_button.run_if(in_state(GameState::Leaderboard)),
+            );
+    }
+}
+
+impl Leaderboard {
+    // A run that didn't score at all never makes it onto the board, even while it has room
+    fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < LEADERBOARD_SIZE
+                || self.entries.iter().any(|entry| score > entry.score))
+    }
+
+    fn insert(&mut self, entry: LeaderboardEntry) {
+        self.entries.push(entry);
+        self.entries.sort_by_key(|entry| Reverse(entry.score));
+        self.entries.truncate(LEADERBOARD_SIZE);
+    }
+
+    // Returns whether the score is a new best for that day, an older day's best is replaced
+    fn record_daily(&mut self, day: u64, score: u32) -> bool {
+        if let Some(best) = &self.daily_best {
+            if best.day == day && best.score >= score {
+                return false;
+            }
+        }
+        self.daily_best = Some(DailyBest { day, score });
+        true
+    }
+
+    fn save(&self) {
+        let result = serde_json::to_string_pretty(self)
+            .map_err(|err| err.to_string())
+            .and_then(|json| fs::write(LEADERBOARD_PATH, json).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            error!("Failed to save the leaderboard: {}", err);
+        }
+    }
+}
+
+fn load_leaderboard(mut commands: Commands) {
+    // A missing file just means that no runs have been recorded yet
+    let leaderboard = match fs::read_to_string(LEADERBOARD_PATH) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|err| {
+            warn!(
+                "Failed to parse the leaderboard, starting a new one: {}",
+                err
+            );
+            Leaderboard::default()
+        }),
+        Err(_) => Leaderboard::default(),
+    };
+    commands.insert_resource(leaderboard);
+}
+
+fn record_run(
+    mut er: EventReader<RunEndedEvent>,
+    mut leaderboard: ResMut<Leaderboard>,
+    rng: Res<GameRng>,
+    game: Res<GameResources>,
+) {
+    for run in er.iter() {
+        // Practice runs start at the boss with every powerup, so they don't count
+        if game.practice_boss {
+            continue;
+        }
+
+        // Seeded runs are kept off the regular high scores
+        if let Some(day) = rng.daily_day {
+            if leaderboard.record_daily(day, run.score) {
+                leaderboard.save();
+            }
+            continue;
+        }
+
+        if !leaderboard.qualifies(run.score) {
+            continue;
+        }
+
+        let date = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        leaderboard.insert(LeaderboardEntry {
+            score: run.score,
+            wave: run.wave,
+            date,
+        });
+        leaderboard.save();
+    }
+}
+
+#[derive(Component)]
+struct BackButton {}
+
+fn init_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+    leaderboard: Res<Leaderboard>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let text_style = |font_size: f32| TextStyle {
+        font: font.clone(),
+        font_size,
+        color: theme.text,
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(
+                    TextBundle::from_section("High scores", text_style(45.0)).with_style(Style {
+                        margin: UiRect::all(Val::Px(10.0)),
+                        ..default()
+                    }),
+                )
+                .insert(ThemedUi {});
+
+            if leaderboard.entries.is_empty() {
+                parent
+                    .spawn(TextBundle::from_section("No runs yet", text_style(30.0)))
+                    .insert(ThemedUi {});
+            }
+            for (rank, entry) in leaderboard.entries.iter().enumerate() {
+                parent
+                    .spawn(TextBundle::from_section(
+                        format!(
+                            "{}. {} - wave {} - {}",
+                            rank + 1,
+                            entry.score,
+                            entry.wave,
+                            format_date(entry.date)
+                        ),
+                        text_style(30.0),
+                    ))
+                    .insert(ThemedUi {});
+            }
+
+            if let Some(best) = &leaderboard.daily_best {
+                parent
+                    .spawn(
+                        TextBundle::from_section(
+                            format!(
+                                "Daily best ({}): {}",
+                                format_date(best.day * SECONDS_PER_DAY),
+                                best.score
+                            ),
+                            text_style(30.0),
+                        )
+                        .with_style(Style {
+                            margin: UiRect::top(Val::Px(10.0)),
+                            ..default()
+                        }),
+                    )
+                    .insert(ThemedUi {});
+            }
+
+            spawn_button(parent, &asset_server, &theme, "Back", BackButton {});
+        })
+        .insert(LeaderboardUiRoot {});
+}
+
+fn destroy_ui(mut commands: Commands, root_query: Query<Entity, With<LeaderboardUiRoot>>) {
+    for ui in root_query.iter() {
+        commands.entity(ui).despawn_recursive();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_back_button(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<BackButton>),
+    >,
+    theme: Res<Theme>,
+    input: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::Menu);
+    }
+
+    for (interaction, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = theme.button_pressed.into();
+                next_state.set(GameState::Menu);
+            }
+            _ => {
+                *color = theme.button.into();
+            }
+        }
+    }
+}
+
+// Formats a unix timestamp as YYYY-MM-DD (UTC), see http://howardhinnant.github.io/date_algorithms.html
+fn format_date(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_runs_that_scored_qualify() {
+        let leaderboard = Leaderboard::default();
+        assert!(!leaderboard.qualifies(0));
+        assert!(leaderboard.qualifies(1));
+    }
+}
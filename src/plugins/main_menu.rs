@@ -1,36 +1,55 @@
 use bevy::{
     prelude::{
-        default, in_state, App, AssetServer, BuildChildren, Button, ButtonBundle, Changed, Color,
-        Commands, Component, IntoSystemConfigs, NextState, NodeBundle, OnEnter, OnExit, Plugin,
-        Query, Res, ResMut, TextBundle, Update, With, Entity, DespawnRecursiveExt,
+        default, in_state, App, AssetServer, BuildChildren, Button, ButtonBundle, Changed,
+        ChildBuilder, Commands, Component, DespawnRecursiveExt, Entity, IntoSystemConfigs,
+        NextState, NodeBundle, OnEnter, OnExit, Plugin, Query, Res, ResMut, TextBundle, Update,
+        With,
     },
     text::TextStyle,
-    ui::{AlignItems, BackgroundColor, BorderColor, Interaction, JustifyContent, Style, Val},
+    ui::{
+        AlignItems, BackgroundColor, BorderColor, FlexDirection, Interaction, JustifyContent,
+        Style, UiRect, Val,
+    },
 };
 
-use crate::state::GameState;
-
-const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
-const BUTTON_PRESSED_COLOR: Color = Color::rgb(0.4, 0.7, 0.4);
+use crate::{
+    plugins::enemy_wave_plugin::{boss_wave_index, EnemyAIState},
+    rng::GameRng,
+    state::GameState,
+    theme::{Theme, ThemedUi},
+    GameResources,
+};
 
 #[derive(Component)]
 pub struct MainUiRoot {}
 
+#[derive(Component, Clone, Copy)]
+enum MenuButton {
+    Play,
+    // Local two-player, the second ship flies on the arrow keys or the second gamepad
+    CoOp,
+    DailyChallenge,
+    PracticeBoss,
+    Leaderboard,
+    Options,
+}
+
 pub struct MainMenuPlugin;
 
 impl Plugin for MainMenuPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::Menu), init_ui);
         app.add_systems(OnExit(GameState::Menu), destroy_ui);
-        app.add_systems(Update, (update_buttons).run_if(in_state(GameState::Menu)));
+        app.add_systems(Update, update_buttons.run_if(in_state(GameState::Menu)));
     }
 }
 
-fn init_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn init_ui(mut commands: Commands, asset_server: Res<AssetServer>, theme: Res<Theme>) {
     commands
         .spawn(NodeBundle {
             style: Style {
                 width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
                 align_items: AlignItems::Center,
                 justify_content: JustifyContent::Center,
                 ..default()
@@ -38,54 +57,134 @@ fn init_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
             ..default()
         })
         .with_children(|parent| {
-            parent
-                .spawn(ButtonBundle {
-                    style: Style {
-                        width: Val::Px(200.0),
-                        height: Val::Px(50.0),
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::Center,
-                        ..default()
-                    },
-                    border_color: BorderColor(Color::BLACK),
-                    background_color: BUTTON_COLOR.into(),
-                    ..default()
-                })
-                .with_children(|parent| {
-                    parent.spawn(TextBundle::from_section(
-                        "Play",
-                        TextStyle {
-                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                            font_size: 45.0,
-                            color: Color::rgb(0.9, 0.9, 0.9),
-                        },
-                    ));
-                });
+            spawn_button(parent, &asset_server, &theme, "Play", MenuButton::Play);
+            spawn_button(parent, &asset_server, &theme, "Co-op", MenuButton::CoOp);
+            spawn_button(
+                parent,
+                &asset_server,
+                &theme,
+                "Daily challenge",
+                MenuButton::DailyChallenge,
+            );
+            spawn_button(
+                parent,
+                &asset_server,
+                &theme,
+                "Practice boss",
+                MenuButton::PracticeBoss,
+            );
+            spawn_button(
+                parent,
+                &asset_server,
+                &theme,
+                "Scores",
+                MenuButton::Leaderboard,
+            );
+            spawn_button(
+                parent,
+                &asset_server,
+                &theme,
+                "Options",
+                MenuButton::Options,
+            );
         })
         .insert(MainUiRoot {});
 }
 
+// Spawns a menu button tagged with `button`, so the caller can tell its buttons apart
+pub fn spawn_button(
+    parent: &mut ChildBuilder,
+    asset_server: &AssetServer,
+    theme: &Theme,
+    label: &str,
+    button: impl Component,
+) {
+    parent
+        .spawn(ButtonBundle {
+            style: Style {
+                min_width: Val::Px(200.0),
+                height: Val::Px(50.0),
+                margin: UiRect::all(Val::Px(10.0)),
+                padding: UiRect::horizontal(Val::Px(10.0)),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            border_color: BorderColor(theme.button_border),
+            background_color: theme.button.into(),
+            ..default()
+        })
+        .insert(button)
+        .insert(ThemedUi {})
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section(
+                    label,
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 45.0,
+                        color: theme.text,
+                    },
+                ))
+                .insert(ThemedUi {});
+        });
+}
+
 fn destroy_ui(mut commands: Commands, root_query: Query<Entity, With<MainUiRoot>>) {
     for ui in root_query.iter() {
         commands.entity(ui).despawn_recursive();
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn update_buttons(
     mut interaction_query: Query<
-        (&Interaction, &mut BackgroundColor),
+        (&Interaction, &mut BackgroundColor, &MenuButton),
         (Changed<Interaction>, With<Button>),
     >,
+    theme: Res<Theme>,
+    mut game: ResMut<GameResources>,
+    mut ai_state: ResMut<EnemyAIState>,
+    mut rng: ResMut<GameRng>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    for (interaction, mut color) in &mut interaction_query {
+    for (interaction, mut color, button) in &mut interaction_query {
         match *interaction {
             Interaction::Pressed => {
-                *color = BUTTON_PRESSED_COLOR.into();
-                next_state.set(GameState::Game);
+                *color = theme.button_pressed.into();
+                match button {
+                    MenuButton::Play => {
+                        rng.start_random_run();
+                        game.two_players = false;
+                        game.practice_boss = false;
+                        next_state.set(GameState::Game);
+                    }
+                    MenuButton::CoOp => {
+                        rng.start_random_run();
+                        game.two_players = true;
+                        game.practice_boss = false;
+                        next_state.set(GameState::Game);
+                    }
+                    // Everyone playing on the same day gets the same waves and drops
+                    MenuButton::DailyChallenge => {
+                        rng.start_daily_challenge();
+                        game.two_players = false;
+                        game.practice_boss = false;
+                        next_state.set(GameState::Game);
+                    }
+                    MenuButton::PracticeBoss => {
+                        rng.start_random_run();
+                        game.two_players = false;
+                        game.practice_boss = true;
+                        ai_state.current_wave = boss_wave_index();
+                        next_state.set(GameState::Game);
+                    }
+                    MenuButton::Leaderboard => next_state.set(GameState::Leaderboard),
+                    MenuButton::Options => next_state.set(GameState::Options),
+                }
             }
             _ => {
-                *color = BUTTON_COLOR.into();
+                *color = theme.button.into();
             }
         }
     }
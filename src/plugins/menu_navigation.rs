@@ -0,0 +1,211 @@
+use bevy::{
+    input::gamepad::{GamepadButton, GamepadButtonType, Gamepads},
+    prelude::{
+        in_state, Added, App, Button, Condition, DetectChanges, Entity, GlobalTransform, Input,
+        IntoSystemConfigs, KeyCode, Local, Plugin, Query, Res, ResMut, Resource, Update, With,
+    },
+    ui::{BackgroundColor, Interaction},
+};
+
+use crate::{plugins::rewards::reward_offer_active, state::GameState, theme::Theme};
+
+// The button focused by keyboard/gamepad navigation, as an index into the on-screen buttons
+// ordered from top to bottom
+#[derive(Resource, Default)]
+pub struct MenuSelection {
+    pub index: usize,
+}
+
+enum NavigationInput {
+    Previous,
+    Next,
+    Activate,
+}
+
+pub struct MenuNavigationPlugin;
+
+impl Plugin for MenuNavigationPlugin {
+    fn build(&self, app: &mut App) {
+        // Only the screens outside of a game, in a game the arrow keys fly the second ship. The
+        // reward offer is the exception, the next wave waits for it so it has to be reachable
+        app.init_resource::<MenuSelection>().add_systems(
+            Update,
+            navigate_menu.run_if(
+                in_state(GameState::Menu)
+                    .or_else(in_state(GameState::GameOver))
+                    .or_else(in_state(GameState::Leaderboard))
+                    .or_else(in_state(GameState::Options))
+                    .or_else(in_state(GameState::Game).and_then(reward_offer_active)),
+            ),
+        );
+    }
+}
+
+fn read_navigation_input(
+    keys: &Input<KeyCode>,
+    gamepads: &Gamepads,
+    gamepad_buttons: &Input<GamepadButton>,
+) -> Option<NavigationInput> {
+    let gamepad_pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+
+    if keys.just_pressed(KeyCode::Up) || gamepad_pressed(GamepadButtonType::DPadUp) {
+        Some(NavigationInput::Previous)
+    } else if keys.just_pressed(KeyCode::Down) || gamepad_pressed(GamepadButtonType::DPadDown) {
+        Some(NavigationInput::Next)
+    } else if keys.just_pressed(KeyCode::Return) || gamepad_pressed(GamepadButtonType::South) {
+        Some(NavigationInput::Activate)
+    } else {
+        None
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn navigate_menu(
+    mut selection: ResMut<MenuSelection>,
+    theme: Res<Theme>,
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut buttons: Query<
+        (
+            Entity,
+            &mut Interaction,
+            &mut BackgroundColor,
+            &GlobalTransform,
+        ),
+        With<Button>,
+    >,
+    added_buttons: Query<Entity, Added<Button>>,
+    mut colored_focus: Local<Option<Entity>>,
+) {
+    if buttons.is_empty() {
+        return;
+    }
+    // A new screen was opened, start from its first button
+    if !added_buttons.is_empty() {
+        selection.index = 0;
+    }
+
+    let mut ordered: Vec<(Entity, f32, f32)> = buttons
+        .iter()
+        .map(|(entity, _, _, transform)| {
+            (entity, transform.translation().y, transform.translation().x)
+        })
+        .collect();
+    ordered.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.2.total_cmp(&b.2)));
+
+    // Hovering with the mouse moves the focus as well, so both ways of navigating agree
+    let mut interaction_changed = false;
+    for (index, (entity, _, _)) in ordered.iter().enumerate() {
+        if let Ok((_, interaction, _, _)) = buttons.get_mut(*entity) {
+            if interaction.is_changed() {
+                interaction_changed = true;
+                if *interaction == Interaction::Hovered {
+                    selection.index = index;
+                }
+            }
+        }
+    }
+
+    let count = ordered.len();
+    match read_navigation_input(&keys, &gamepads, &gamepad_buttons) {
+        Some(NavigationInput::Previous) => selection.index = (selection.index + count - 1) % count,
+        Some(NavigationInput::Next) => selection.index = (selection.index + 1) % count,
+        Some(NavigationInput::Activate) => {
+            let focused = ordered[selection.index.min(count - 1)].0;
+            if let Ok((_, mut interaction, _, _)) = buttons.get_mut(focused) {
+                // Reuses each screen's mouse handling, which reacts to pressed buttons
+                *interaction = Interaction::Pressed;
+            }
+        }
+        None => {}
+    }
+    selection.index = selection.index.min(count - 1);
+
+    // Only recolor when something changed, so other styling isn't overwritten every frame
+    let focused = ordered[selection.index].0;
+    let recolor = *colored_focus != Some(focused)
+        || interaction_changed
+        || !added_buttons.is_empty()
+        || theme.is_changed();
+    if !recolor {
+        return;
+    }
+    *colored_focus = Some(focused);
+    for (entity, interaction, mut color, _) in buttons.iter_mut() {
+        *color = if *interaction == Interaction::Pressed {
+            theme.button_pressed.into()
+        } else if entity == focused {
+            theme.button_focused.into()
+        } else {
+            theme.button.into()
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        input::gamepad::{GamepadButton, Gamepads},
+        prelude::{App, Button, GlobalTransform, Input, KeyCode, MinimalPlugins, NextState, State},
+        ui::{BackgroundColor, Interaction},
+    };
+
+    use super::MenuNavigationPlugin;
+    use crate::{plugins::rewards::RewardOffer, state::GameState, theme::Theme};
+
+    fn press_return(app: &mut App) {
+        let mut keys = app.world.resource_mut::<Input<KeyCode>>();
+        keys.reset_all();
+        keys.press(KeyCode::Return);
+        app.update();
+    }
+
+    #[test]
+    fn keyboard_players_can_pick_a_reward() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, MenuNavigationPlugin))
+            .add_state::<GameState>()
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Gamepads>()
+            .init_resource::<Input<GamepadButton>>()
+            .init_resource::<Theme>()
+            .init_resource::<RewardOffer>();
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Game);
+        app.update();
+        assert_eq!(
+            *app.world.resource::<State<GameState>>().get(),
+            GameState::Game
+        );
+
+        let button = app
+            .world
+            .spawn((
+                Button,
+                Interaction::None,
+                BackgroundColor::default(),
+                GlobalTransform::default(),
+            ))
+            .id();
+
+        // Gameplay keeps the arrow keys and enter to itself
+        press_return(&mut app);
+        assert_eq!(
+            *app.world.get::<Interaction>(button).unwrap(),
+            Interaction::None
+        );
+
+        app.world.resource_mut::<RewardOffer>().active = true;
+        press_return(&mut app);
+        assert_eq!(
+            *app.world.get::<Interaction>(button).unwrap(),
+            Interaction::Pressed
+        );
+    }
+}
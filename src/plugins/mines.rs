@@ -0,0 +1,282 @@
+use bevy::{
+    prelude::{
+        in_state, shape, App, Assets, BuildChildren, Children, Color, Commands, Component,
+        DespawnRecursiveExt, Entity, EventWriter, IntoSystemConfigs, Mesh, OnExit, PbrBundle,
+        Plugin, Query, Res, ResMut, StandardMaterial, Transform, Update, Visibility, With, Without,
+    },
+    time::Time,
+};
+use bevy_rapier3d::prelude::{Collider, RapierContext, Sensor};
+
+use crate::{
+    camera::CameraShakeEvent,
+    combat::{capped_delta_seconds, Damageable, EntityDeath, ParticleHitEffect, PlayerHitEvent},
+    countdown::StartCountdown,
+    enemy::Enemy,
+    plugins::{achievements::Achievements, enemy_wave_plugin::MoveToTarget, tutorial::Tutorial},
+    settings::Settings,
+    state::GameState,
+    theme::Theme,
+    GameResources,
+};
+
+const MINE_DROP_INTERVAL_S: f32 = 4.0;
+// Mines drift down to the player's row before settling, since the player can only move sideways
+const MINE_DRIFT_VELOCITY: f32 = 2.5;
+const MINE_FUSE_S: f32 = 6.0;
+// Coming close arms the mine, leaving only this long to get out of the blast
+const MINE_TRIGGERED_FUSE_S: f32 = 0.5;
+const MINE_TRIGGER_RADIUS: f32 = 1.0;
+const MINE_BLAST_RADIUS: f32 = 0.8;
+const MINE_RADIUS: f32 = 0.15;
+const MINE_DAMAGE: u32 = 1;
+const MAX_LIVE_MINES: usize = 6;
+// The light blinks slowly while the fuse is long and speeds up as it runs down
+const MINE_BLINK_SLOWEST_S: f32 = 0.5;
+const MINE_BLINK_FASTEST_S: f32 = 0.05;
+
+// Support enemies that drop a mine every so often, on top of their regular shots
+#[derive(Component)]
+pub struct MineLayer {
+    pub drop_timer: f32,
+}
+
+impl Default for MineLayer {
+    fn default() -> Self {
+        Self {
+            drop_timer: MINE_DROP_INTERVAL_S,
+        }
+    }
+}
+
+// A stationary hazard that explodes when the player touches it or once the fuse runs out
+#[derive(Component)]
+pub struct Mine {
+    pub fuse: f32,
+    pub triggered: bool,
+}
+
+// Moving down towards the player's row, the mine stays put once it gets there
+#[derive(Component)]
+struct Drifting {
+    target_z: f32,
+}
+
+#[derive(Component)]
+struct MineLight {
+    blink_timer: f32,
+}
+
+pub struct MinePlugin;
+
+impl Plugin for MinePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnExit(GameState::Game), destroy_mines)
+            .add_systems(
+                Update,
+                (lay_mines, drift_mines, update_mines, blink_mines)
+                    .chain()
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn lay_mines(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    theme: Res<Theme>,
+    game: Res<GameResources>,
+    countdown: Option<Res<StartCountdown>>,
+    tutorial: Res<Tutorial>,
+    mut layers: Query<(&mut MineLayer, &Transform), (With<Enemy>, Without<MoveToTarget>)>,
+    players: Query<&Transform, Without<Enemy>>,
+    mines: Query<With<Mine>>,
+) {
+    if countdown.is_some() || tutorial.holds_fire() {
+        return;
+    }
+
+    let delta_time = capped_delta_seconds(&time) * settings.game_speed.0;
+    let mut live_mines = mines.iter().count();
+    for (mut layer, transform) in layers.iter_mut() {
+        layer.drop_timer -= delta_time;
+        if layer.drop_timer > 0.0 {
+            continue;
+        }
+        layer.drop_timer = MINE_DROP_INTERVAL_S;
+        if live_mines >= MAX_LIVE_MINES {
+            continue;
+        }
+        let Some(player_position) = game.nearest_player(transform.translation, &players) else {
+            continue;
+        };
+        live_mines += 1;
+
+        let light = commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::UVSphere {
+                    radius: MINE_RADIUS * 0.4,
+                    ..Default::default()
+                })),
+                material: materials.add(StandardMaterial {
+                    emissive: theme.enemy_bullet,
+                    ..Default::default()
+                }),
+                transform: Transform::from_xyz(0.0, MINE_RADIUS, 0.0),
+                ..Default::default()
+            })
+            .insert(MineLight { blink_timer: 0.0 })
+            .id();
+        commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::UVSphere {
+                    radius: MINE_RADIUS,
+                    ..Default::default()
+                })),
+                material: materials.add(StandardMaterial {
+                    base_color: Color::rgb(0.2, 0.2, 0.22),
+                    metallic: 0.8,
+                    perceptual_roughness: 0.4,
+                    ..Default::default()
+                }),
+                transform: Transform::from_translation(transform.translation),
+                ..Default::default()
+            })
+            .insert(Mine {
+                fuse: MINE_FUSE_S,
+                triggered: false,
+            })
+            .insert(Drifting {
+                target_z: player_position.z,
+            })
+            .insert(Collider::ball(MINE_RADIUS))
+            .insert(Sensor {})
+            .add_child(light);
+    }
+}
+
+fn drift_mines(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut mines: Query<(Entity, &Drifting, &mut Transform), With<Mine>>,
+) {
+    let step = MINE_DRIFT_VELOCITY * capped_delta_seconds(&time) * settings.game_speed.0;
+    for (mine_entity, drifting, mut transform) in mines.iter_mut() {
+        transform.translation.z += step;
+        if transform.translation.z >= drifting.target_z {
+            transform.translation.z = drifting.target_z;
+            commands.entity(mine_entity).remove::<Drifting>();
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_mines(
+    mut commands: Commands,
+    rapier_context: Res<RapierContext>,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    game: Res<GameResources>,
+    mut ev: EventWriter<CameraShakeEvent>,
+    mut player_hit: EventWriter<PlayerHitEvent>,
+    mut achievements: ResMut<Achievements>,
+    mut mines: Query<(Entity, &mut Mine, &Transform), Without<Drifting>>,
+    mut players: Query<(&mut Damageable, &Transform), Without<Mine>>,
+) {
+    let delta_time = capped_delta_seconds(&time) * settings.game_speed.0;
+    for (mine_entity, mut mine, transform) in mines.iter_mut() {
+        let touching =
+            |player: Entity| rapier_context.intersection_pair(player, mine_entity) == Some(true);
+        let distance = |player: Entity| {
+            players
+                .get(player)
+                .map_or(f32::INFINITY, |(_, player_transform)| {
+                    player_transform.translation.distance(transform.translation)
+                })
+        };
+        let nearby = game
+            .players
+            .iter()
+            .any(|player| distance(*player) <= MINE_TRIGGER_RADIUS);
+        if nearby && !mine.triggered {
+            mine.triggered = true;
+            mine.fuse = mine.fuse.min(MINE_TRIGGERED_FUSE_S);
+        }
+
+        mine.fuse -= delta_time;
+        if !game.players.iter().any(|player| touching(*player)) && mine.fuse > 0.0 {
+            continue;
+        }
+
+        // Detonate, hurting every ship caught in the blast
+        let caught: Vec<Entity> = game
+            .players
+            .iter()
+            .copied()
+            .filter(|player| touching(*player) || distance(*player) <= MINE_BLAST_RADIUS)
+            .collect();
+        commands.entity(mine_entity).despawn_recursive();
+        commands.spawn(ParticleHitEffect {
+            position: transform.translation,
+            is_large: true,
+        });
+        ev.send(CameraShakeEvent { intensity: 0.7 });
+
+        for player in caught {
+            let Ok((mut damageable, player_transform)) = players.get_mut(player) else {
+                continue;
+            };
+            if damageable.health == 0 {
+                continue;
+            }
+
+            damageable.health = damageable.health.saturating_sub(MINE_DAMAGE);
+            player_hit.send(PlayerHitEvent {});
+            achievements.record_player_hit();
+            if damageable.health == 0 {
+                commands.entity(player).despawn_recursive();
+                commands.spawn(EntityDeath {
+                    position: player_transform.translation,
+                    is_player: true,
+                });
+            }
+        }
+    }
+}
+
+fn blink_mines(
+    time: Res<Time>,
+    mines: Query<(&Mine, &Children)>,
+    mut lights: Query<(&mut MineLight, &mut Visibility)>,
+) {
+    for (mine, children) in mines.iter() {
+        let fuse_left = (mine.fuse / MINE_FUSE_S).clamp(0.0, 1.0);
+        let interval =
+            MINE_BLINK_FASTEST_S + (MINE_BLINK_SLOWEST_S - MINE_BLINK_FASTEST_S) * fuse_left;
+        for child in children.iter() {
+            let Ok((mut light, mut visibility)) = lights.get_mut(*child) else {
+                continue;
+            };
+            light.blink_timer += time.delta_seconds();
+            if light.blink_timer < interval {
+                continue;
+            }
+            light.blink_timer = 0.0;
+            *visibility = match *visibility {
+                Visibility::Hidden => Visibility::Inherited,
+                _ => Visibility::Hidden,
+            };
+        }
+    }
+}
+
+fn destroy_mines(mut commands: Commands, mines: Query<Entity, With<Mine>>) {
+    for mine in mines.iter() {
+        commands.entity(mine).despawn_recursive();
+    }
+}
@@ -1,3 +1,21 @@
+pub mod achievements;
+pub mod asteroids;
+pub mod boss;
+pub mod damage_numbers;
 pub mod enemy_wave_plugin;
+pub mod game_over;
+pub mod graze;
+pub mod health_bars;
+pub mod heat_bar;
+pub mod help_overlay;
+pub mod last_stand;
+pub mod leaderboard;
+pub mod menu_navigation;
+pub mod mines;
+pub mod music;
+pub mod options_menu;
 pub mod powerups;
+pub mod rewards;
+pub mod support;
+pub mod tutorial;
 pub mod main_menu;
\ No newline at end of file
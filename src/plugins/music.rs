@@ -0,0 +1,152 @@
+use bevy::{
+    audio::{AudioBundle, AudioSink, AudioSinkPlayback, PlaybackSettings, Volume},
+    prelude::{
+        App, AssetServer, Commands, Component, DespawnRecursiveExt, Entity, EventReader,
+        IntoSystemConfigs, Plugin, Query, Res, ResMut, Resource, State, Update, With,
+    },
+    time::Time,
+};
+
+use crate::{
+    plugins::{boss::BossAttackState, enemy_wave_plugin::NewWaveEvent},
+    state::GameState,
+};
+
+const CROSSFADE_S: f32 = 1.5;
+const WAVE_CLEAR_STING: &str = "audio/wave_clear.ogg";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MusicContext {
+    Menu,
+    Game,
+    Boss,
+}
+
+impl MusicContext {
+    fn track(&self) -> &'static str {
+        match self {
+            MusicContext::Menu => "audio/menu.ogg",
+            MusicContext::Game => "audio/game.ogg",
+            MusicContext::Boss => "audio/boss.ogg",
+        }
+    }
+}
+
+// Keeps track of which music is playing, so that the track is only swapped when the context
+// actually changes
+#[derive(Resource, Default)]
+pub struct MusicController {
+    pub context: Option<MusicContext>,
+    track: Option<Entity>,
+}
+
+#[derive(Component)]
+struct MusicTrack {
+    volume: f32,
+    fading_out: bool,
+}
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MusicController>().add_systems(
+            Update,
+            (play_wave_sting, update_music_context, crossfade_tracks).chain(),
+        );
+    }
+}
+
+// Every screen outside of a run shares the menu track
+fn music_context(state: GameState, boss_active: bool) -> MusicContext {
+    match state {
+        GameState::Game if boss_active => MusicContext::Boss,
+        GameState::Game => MusicContext::Game,
+        _ => MusicContext::Menu,
+    }
+}
+
+// The wave sent when a run starts arrives while the menu track is still the current one, so
+// only cleared waves get a sting
+fn play_wave_sting(
+    mut commands: Commands,
+    mut er: EventReader<NewWaveEvent>,
+    asset_server: Res<AssetServer>,
+    controller: Res<MusicController>,
+) {
+    if er.iter().count() == 0 || controller.context == Some(MusicContext::Menu) {
+        return;
+    }
+
+    commands.spawn(AudioBundle {
+        source: asset_server.load(WAVE_CLEAR_STING),
+        settings: PlaybackSettings::DESPAWN,
+    });
+}
+
+fn update_music_context(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    state: Res<State<GameState>>,
+    mut controller: ResMut<MusicController>,
+    bosses: Query<(), With<BossAttackState>>,
+    mut tracks: Query<&mut MusicTrack>,
+) {
+    let context = music_context(*state.get(), !bosses.is_empty());
+    if controller.context == Some(context) {
+        return;
+    }
+
+    // The old track fades out while the new one fades in
+    if let Some(mut track) = controller.track.and_then(|id| tracks.get_mut(id).ok()) {
+        track.fading_out = true;
+    }
+    let track = commands
+        .spawn(AudioBundle {
+            source: asset_server.load(context.track()),
+            settings: PlaybackSettings::LOOP.with_volume(Volume::new_relative(0.0)),
+        })
+        .insert(MusicTrack {
+            volume: 0.0,
+            fading_out: false,
+        })
+        .id();
+    controller.context = Some(context);
+    controller.track = Some(track);
+}
+
+// Uses the real frame time so that the music keeps fading while the game is paused
+fn crossfade_tracks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut tracks: Query<(Entity, &mut MusicTrack, Option<&AudioSink>)>,
+) {
+    let step = time.raw_delta_seconds() / CROSSFADE_S;
+    for (entity, mut track, sink) in tracks.iter_mut() {
+        if track.fading_out {
+            track.volume -= step;
+            if track.volume <= 0.0 {
+                commands.entity(entity).despawn_recursive();
+                continue;
+            }
+        } else {
+            track.volume = f32::min(track.volume + step, 1.0);
+        }
+        if let Some(sink) = sink {
+            sink.set_volume(track.volume);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boss_music_only_plays_during_a_run() {
+        assert_eq!(music_context(GameState::Game, true), MusicContext::Boss);
+        assert_eq!(music_context(GameState::Game, false), MusicContext::Game);
+        assert_eq!(music_context(GameState::GameOver, true), MusicContext::Menu);
+        assert_eq!(music_context(GameState::Options, false), MusicContext::Menu);
+    }
+}
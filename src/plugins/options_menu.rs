@@ -0,0 +1,207 @@
+use bevy::{
+    prelude::{
+        default, in_state, App, AssetServer, BuildChildren, Button, Changed, Children, Commands,
+        Component, DespawnRecursiveExt, DetectChanges, Entity, Input, IntoSystemConfigs, KeyCode,
+        NextState, NodeBundle, OnEnter, OnExit, Plugin, Query, Res, ResMut, Update, With,
+    },
+    text::Text,
+    ui::{AlignItems, BackgroundColor, FlexDirection, Interaction, JustifyContent, Style, Val},
+};
+
+use crate::{plugins::main_menu::spawn_button, settings::Settings, state::GameState, theme::Theme};
+
+#[derive(Component)]
+struct OptionsUiRoot {}
+
+// Every button but Back cycles or toggles the setting it shows
+#[derive(Component, Clone, Copy)]
+enum OptionsButton {
+    Theme,
+    ColorBlind,
+    MoveSpeed,
+    InvertControls,
+    GameSpeed,
+    CameraView,
+    FrameLimit,
+    WeaponModel,
+    ScreenShake,
+    ReducedMotion,
+    DamageNumbers,
+    PauseOnHelp,
+    Back,
+}
+
+const SETTINGS: [OptionsButton; 12] = [
+    OptionsButton::Theme,
+    OptionsButton::ColorBlind,
+    OptionsButton::MoveSpeed,
+    OptionsButton::InvertControls,
+    OptionsButton::GameSpeed,
+    OptionsButton::CameraView,
+    OptionsButton::FrameLimit,
+    OptionsButton::WeaponModel,
+    OptionsButton::ScreenShake,
+    OptionsButton::ReducedMotion,
+    OptionsButton::DamageNumbers,
+    OptionsButton::PauseOnHelp,
+];
+
+pub struct OptionsMenuPlugin;
+
+impl Plugin for OptionsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Options), init_ui)
+            .add_systems(OnExit(GameState::Options), destroy_ui)
+            .add_systems(
+                Update,
+                (update_buttons, update_labels).run_if(in_state(GameState::Options)),
+            );
+    }
+}
+
+fn init_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+    settings: Res<Settings>,
+) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for button in SETTINGS {
+                spawn_button(
+                    parent,
+                    &asset_server,
+                    &theme,
+                    &setting_label(button, &settings),
+                    button,
+                );
+            }
+            spawn_button(parent, &asset_server, &theme, "Back", OptionsButton::Back);
+        })
+        .insert(OptionsUiRoot {});
+}
+
+// Labels for the buttons that show the current value of a setting
+fn setting_label(button: OptionsButton, settings: &Settings) -> String {
+    let on_off = |on: bool| if on { "On" } else { "Off" };
+    match button {
+        OptionsButton::Theme => format!("Theme: {}", settings.theme.name()),
+        OptionsButton::ColorBlind => format!("Color-blind: {}", on_off(settings.color_blind)),
+        OptionsButton::MoveSpeed => {
+            format!("Move speed: {:.2}x", settings.controls.speed_multiplier)
+        }
+        OptionsButton::InvertControls => format!(
+            "Invert controls: {}",
+            on_off(settings.controls.invert_horizontal)
+        ),
+        OptionsButton::GameSpeed => format!("Game speed: {:.2}x", settings.game_speed.0),
+        OptionsButton::CameraView => format!("View: {}", settings.camera_view.name()),
+        OptionsButton::FrameLimit => format!("Frame rate: {}", settings.frame_limit.name()),
+        OptionsButton::WeaponModel => format!("Weapon: {}", settings.weapon_model.name()),
+        OptionsButton::ScreenShake if settings.shake_scale <= 0.0 => {
+            "Screen shake: Off".to_string()
+        }
+        OptionsButton::ScreenShake => {
+            format!("Screen shake: {:.0}%", settings.shake_scale * 100.0)
+        }
+        OptionsButton::ReducedMotion => {
+            format!("Reduced motion: {}", on_off(settings.reduced_motion))
+        }
+        OptionsButton::DamageNumbers => {
+            format!("Damage numbers: {}", on_off(settings.damage_numbers))
+        }
+        OptionsButton::PauseOnHelp => {
+            format!("Pause on help: {}", on_off(settings.pause_on_help))
+        }
+        OptionsButton::Back => "Back".to_string(),
+    }
+}
+
+fn destroy_ui(mut commands: Commands, root_query: Query<Entity, With<OptionsUiRoot>>) {
+    for ui in root_query.iter() {
+        commands.entity(ui).despawn_recursive();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_buttons(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &OptionsButton),
+        (Changed<Interaction>, With<Button>),
+    >,
+    theme: Res<Theme>,
+    input: Res<Input<KeyCode>>,
+    mut settings: ResMut<Settings>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::Menu);
+    }
+
+    for (interaction, mut color, button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = theme.button_pressed.into();
+                match button {
+                    OptionsButton::Theme => settings.theme = settings.theme.next(),
+                    OptionsButton::ColorBlind => settings.color_blind = !settings.color_blind,
+                    OptionsButton::MoveSpeed => {
+                        settings.controls.speed_multiplier =
+                            settings.controls.next_speed_multiplier()
+                    }
+                    OptionsButton::InvertControls => {
+                        settings.controls.invert_horizontal = !settings.controls.invert_horizontal
+                    }
+                    OptionsButton::GameSpeed => settings.game_speed = settings.game_speed.next(),
+                    OptionsButton::CameraView => settings.camera_view = settings.camera_view.next(),
+                    OptionsButton::FrameLimit => settings.frame_limit = settings.frame_limit.next(),
+                    OptionsButton::WeaponModel => {
+                        settings.weapon_model = settings.weapon_model.next()
+                    }
+                    OptionsButton::ScreenShake => {
+                        settings.shake_scale = settings.next_shake_scale()
+                    }
+                    OptionsButton::ReducedMotion => {
+                        settings.reduced_motion = !settings.reduced_motion
+                    }
+                    OptionsButton::DamageNumbers => {
+                        settings.damage_numbers = !settings.damage_numbers
+                    }
+                    OptionsButton::PauseOnHelp => settings.pause_on_help = !settings.pause_on_help,
+                    OptionsButton::Back => next_state.set(GameState::Menu),
+                }
+            }
+            _ => {
+                *color = theme.button.into();
+            }
+        }
+    }
+}
+
+fn update_labels(
+    settings: Res<Settings>,
+    buttons: Query<(&OptionsButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (button, children) in buttons.iter() {
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = setting_label(*button, &settings);
+            }
+        }
+    }
+}
@@ -1,29 +1,48 @@
-use std::borrow::BorrowMut;
-
 use bevy::{
     prelude::{
-        shape, Assets, BuildChildren, Color, Commands, Component, DespawnRecursiveExt, Entity,
-        IntoSystemConfigs, Mesh, PbrBundle, Plugin, Quat, Query, Res, ResMut, SpatialBundle,
-        StandardMaterial, Transform, Update, Vec3, With, Without, in_state,
+        default, in_state, shape, AssetServer, Assets, BuildChildren, Color, Commands, Component,
+        DespawnRecursiveExt, Entity, IntoSystemConfigs, Mesh, OnEnter, OnExit, Or, PbrBundle,
+        Plugin, Quat, Query, Res, ResMut, Resource, SpatialBundle, StandardMaterial, TextBundle,
+        Transform, Update, Vec3, With, Without,
     },
+    text::{Text, TextSection, TextStyle},
     time::Time,
     transform::TransformBundle,
+    ui::{PositionType, Style, Val},
 };
 use bevy_rapier3d::prelude::{Collider, GravityScale, RapierContext, RigidBody, Sensor, Velocity};
-use rand::Rng;
+use rand::{seq::SliceRandom, Rng};
 
-use crate::{combat::EntityDeath, state::GameState, Player};
+use crate::{
+    combat::{EntityDeath, ParticleHitEffect},
+    rng::GameRng,
+    state::GameState,
+    theme::Theme,
+    GameResources, Player,
+};
 
 #[derive(PartialEq, Eq, Clone)]
 pub enum Powerup {
     DoubleShot,
     TripleShot,
+    // Enemy deaths explode and damage nearby enemies
+    ExplosiveRounds,
+    // Pulls nearby pickups towards the player
+    Magnet,
+    // Hits set enemies on fire, damaging them over time
+    FireRounds,
 }
 
-#[derive(PartialEq, Eq, Clone)]
-struct DoubleShot {}
-#[derive(PartialEq, Eq, Clone)]
-struct TripleShot {}
+// Pickups within this distance of a player with the magnet home in on them
+const MAGNET_RADIUS: f32 = 4.0;
+const MAGNET_PULL_VELOCITY: f32 = 6.0;
+
+// Score gems are dropped independently of the powerups
+const SCORE_GEM_DROP_CHANCE: f64 = 0.25;
+const SCORE_GEM_VALUE: u32 = 100;
+const SCORE_GEM_FALL_VELOCITY: f32 = 1.5;
+// Gems further down the screen than this have been missed
+const SCORE_GEM_MAX_Z: f32 = 12.0;
 
 #[derive(Component, Clone)]
 pub struct PowerupComponent {
@@ -31,49 +50,172 @@ pub struct PowerupComponent {
     time_left: f32,
 }
 
+// Every powerup the player currently has, each running out on its own timer
+#[derive(Component, Default, Clone)]
+pub struct ActivePowerups {
+    pub powerups: Vec<PowerupComponent>,
+}
+
+#[derive(Component)]
+struct PowerupHud {}
+
+#[derive(Component)]
+pub struct ScoreGem {
+    pub value: u32,
+}
+
+#[derive(Resource)]
+pub struct PowerupDropRate {
+    pub base_chance: f64,
+    // Added to the drop chance for every kill since the last drop
+    pub chance_per_dry_kill: f64,
+    // A drop is guaranteed once this many kills have passed without one
+    pub guaranteed_after_kills: u32,
+    pub enabled: Vec<Powerup>,
+}
+
+#[derive(Resource, Default)]
+struct PowerupDropTracker {
+    kills_since_drop: u32,
+}
+
 pub struct PowerupPlugin;
 
 impl Plugin for PowerupPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_systems(
-            Update,
-            (update_powerups, spawn_powerups, detect_powerup_collisions).run_if(in_state(GameState::Game)),
-        );
+        app.init_resource::<PowerupDropRate>()
+            .init_resource::<PowerupDropTracker>()
+            .add_systems(OnEnter(GameState::Game), (reset_drop_tracker, init_hud))
+            .add_systems(OnExit(GameState::Game), (destroy_hud, destroy_pickups))
+            .add_systems(
+                Update,
+                (
+                    update_powerups,
+                    spawn_score_gems.before(spawn_powerups),
+                    spawn_powerups,
+                    attract_powerups,
+                    detect_powerup_collisions,
+                    collect_score_gems,
+                    update_hud,
+                )
+                    .run_if(in_state(GameState::Game)),
+            );
     }
 }
 
-fn update_powerups(
-    mut commands: Commands,
-    time: Res<Time>,
-    mut powerups: Query<(Entity, &mut PowerupComponent), With<Player>>,
-) {
-    for (entity, mut powerup) in powerups.iter_mut() {
-        powerup.time_left -= time.delta_seconds();
-        if powerup.time_left < 0.0 {
-            commands.entity(entity).remove::<PowerupComponent>();
+impl Powerup {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Powerup::DoubleShot => "Double shot",
+            Powerup::TripleShot => "Triple shot",
+            Powerup::ExplosiveRounds => "Explosive rounds",
+            Powerup::Magnet => "Magnet",
+            Powerup::FireRounds => "Fire rounds",
+        }
+    }
+
+    fn is_shot_powerup(&self) -> bool {
+        matches!(self, Powerup::DoubleShot | Powerup::TripleShot)
+    }
+}
+
+impl ActivePowerups {
+    // Every powerup at once, for the practice boss fight
+    pub fn fully_equipped(duration: f32) -> Self {
+        Self {
+            powerups: [
+                Powerup::TripleShot,
+                Powerup::ExplosiveRounds,
+                Powerup::Magnet,
+                Powerup::FireRounds,
+            ]
+            .into_iter()
+            .map(|powerup| PowerupComponent {
+                powerup,
+                time_left: duration,
+            })
+            .collect(),
         }
     }
+
+    pub fn has(&self, powerup: &Powerup) -> bool {
+        self.powerups
+            .iter()
+            .any(|active| active.powerup == *powerup)
+    }
+
+    fn add(&mut self, pickup: PowerupComponent) {
+        // The shot powerups share a slot, where a second double shot upgrades it to a triple shot
+        if pickup.powerup.is_shot_powerup() {
+            if let Some(current) = self
+                .powerups
+                .iter_mut()
+                .find(|active| active.powerup.is_shot_powerup())
+            {
+                if current.powerup == Powerup::DoubleShot {
+                    current.powerup = Powerup::TripleShot;
+                }
+                current.time_left += pickup.time_left;
+                return;
+            }
+        }
+
+        // Picking up a powerup that is already active extends it
+        match self
+            .powerups
+            .iter_mut()
+            .find(|active| active.powerup == pickup.powerup)
+        {
+            Some(active) => active.time_left += pickup.time_left,
+            None => self.powerups.push(pickup),
+        }
+    }
+}
+
+fn update_powerups(time: Res<Time>, mut players: Query<&mut ActivePowerups, With<Player>>) {
+    for mut active in players.iter_mut() {
+        for powerup in active.powerups.iter_mut() {
+            powerup.time_left -= time.delta_seconds();
+        }
+        active.powerups.retain(|powerup| powerup.time_left >= 0.0);
+    }
+}
+
+fn reset_drop_tracker(mut tracker: ResMut<PowerupDropTracker>) {
+    *tracker = PowerupDropTracker::default();
 }
 
 fn spawn_powerups(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    drop_rate: Res<PowerupDropRate>,
+    mut tracker: ResMut<PowerupDropTracker>,
+    mut rng: ResMut<GameRng>,
     deaths: Query<(Entity, &EntityDeath)>,
 ) {
-    let mut rng = rand::thread_rng();
     for (death_entity, death) in deaths.iter() {
         if death.is_player {
             continue;
         }
 
-        let prob = rng.gen::<f64>();
-        if prob < 0.1 {
+        let chance =
+            drop_rate.base_chance + drop_rate.chance_per_dry_kill * tracker.kills_since_drop as f64;
+        let guaranteed = tracker.kills_since_drop + 1 >= drop_rate.guaranteed_after_kills;
+        let powerup = if guaranteed || rng.drops().gen::<f64>() < chance {
+            drop_rate.enabled.choose(rng.drops())
+        } else {
+            None
+        };
+
+        if let Some(powerup) = powerup {
+            tracker.kills_since_drop = 0;
+
             // Spawn a new powerup
             commands
                 .spawn(SpatialBundle::default())
                 .insert(PowerupComponent {
-                    powerup: Powerup::DoubleShot,
+                    powerup: powerup.clone(),
                     time_left: 5.0,
                 })
                 .insert(RigidBody::Dynamic)
@@ -104,35 +246,197 @@ fn spawn_powerups(
                         ..Default::default()
                     });
                 });
+        } else {
+            tracker.kills_since_drop += 1;
         }
         commands.entity(death_entity).despawn_recursive();
     }
 }
 
-// Sees if the player collides with a powerup
-fn detect_powerup_collisions(
+#[allow(clippy::type_complexity)]
+fn attract_powerups(
+    players: Query<(&Transform, &ActivePowerups), With<Player>>,
+    mut pickups: Query<(&Transform, &mut Velocity), (With<PowerupComponent>, Without<Player>)>,
+) {
+    // Each pickup heads for the closest ship with a magnet in range
+    let magnets: Vec<_> = players
+        .iter()
+        .filter(|(_, active)| active.has(&Powerup::Magnet))
+        .map(|(transform, _)| transform.translation)
+        .collect();
+
+    for (transform, mut velocity) in pickups.iter_mut() {
+        let closest = magnets
+            .iter()
+            .map(|magnet| *magnet - transform.translation)
+            .min_by(|a, b| a.length().total_cmp(&b.length()));
+        if let Some(offset) = closest.filter(|offset| offset.length() <= MAGNET_RADIUS) {
+            velocity.linvel = offset.normalize_or_zero() * MAGNET_PULL_VELOCITY;
+        }
+    }
+}
+
+// Runs before spawn_powerups, which cleans up the deaths
+fn spawn_score_gems(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut rng: ResMut<GameRng>,
+    deaths: Query<&EntityDeath>,
+) {
+    for death in deaths.iter() {
+        if death.is_player || rng.drops().gen::<f64>() >= SCORE_GEM_DROP_CHANCE {
+            continue;
+        }
+
+        commands
+            .spawn(SpatialBundle::default())
+            .insert(ScoreGem {
+                value: SCORE_GEM_VALUE,
+            })
+            .insert(RigidBody::Dynamic)
+            .insert(GravityScale(0.0))
+            .insert(Collider::cuboid(0.1, 0.1, 0.1))
+            .insert(Sensor)
+            .insert(TransformBundle::from(Transform::from_translation(
+                death.position,
+            )))
+            .insert(Velocity {
+                linvel: Vec3::new(0.0, 0.0, SCORE_GEM_FALL_VELOCITY),
+                angvel: Vec3::new(0.0, 2.0, 0.0),
+            })
+            .with_children(|children| {
+                children.spawn(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Cube { size: 0.15 })),
+                    transform: Transform::from_rotation(Quat::from_rotation_x(
+                        45.0f32.to_radians(),
+                    )),
+                    material: materials.add(StandardMaterial {
+                        emissive: Color::rgb_linear(20.0, 14.0, 1.0),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                });
+            });
+    }
+}
+
+fn collect_score_gems(
     mut commands: Commands,
     rapier_context: Res<RapierContext>,
-    mut player_query: Query<(Entity, &mut Player, Option<&mut PowerupComponent>)>,
-    mut powerups: Query<(Entity, &mut PowerupComponent), (With<Collider>, Without<Player>)>,
+    mut game: ResMut<GameResources>,
+    players: Query<Entity, With<Player>>,
+    gems: Query<(Entity, &ScoreGem, &Transform)>,
 ) {
-    let Ok(mut player) = player_query.get_single_mut() else {
-        return;
-    };
+    for (gem_entity, gem, transform) in gems.iter() {
+        let collected = players
+            .iter()
+            .any(|player| rapier_context.intersection_pair(gem_entity, player) == Some(true));
+        if collected {
+            game.score += gem.value;
+            commands.spawn(ParticleHitEffect {
+                position: transform.translation,
+                is_large: false,
+            });
+            commands.entity(gem_entity).despawn_recursive();
+        } else if transform.translation.z > SCORE_GEM_MAX_Z {
+            commands.entity(gem_entity).despawn_recursive();
+        }
+    }
+}
 
-    for (power_entity, powerup) in powerups.iter_mut() {
-        if rapier_context.intersection_pair(power_entity, player.0) == Some(true) {
-            // Upgrades to triple-shot if the player already has a double shot
-            if let Some(current_powerup) = player.2.borrow_mut() {
-                if current_powerup.powerup == Powerup::DoubleShot {
-                    current_powerup.powerup = Powerup::TripleShot;
-                    println!("Activating triple shot");
-                }
-                current_powerup.time_left += powerup.time_left;
-            } else {
-                commands.entity(player.0).insert(powerup.clone());
-            }
+impl Default for PowerupDropRate {
+    fn default() -> Self {
+        Self {
+            base_chance: 0.1,
+            chance_per_dry_kill: 0.02,
+            guaranteed_after_kills: 15,
+            enabled: vec![
+                Powerup::DoubleShot,
+                Powerup::TripleShot,
+                Powerup::ExplosiveRounds,
+                Powerup::Magnet,
+                Powerup::FireRounds,
+            ],
+        }
+    }
+}
+
+// Sees if a player collides with a powerup, only the ship that touches it gets it
+#[allow(clippy::type_complexity)]
+fn detect_powerup_collisions(
+    mut commands: Commands,
+    rapier_context: Res<RapierContext>,
+    mut players: Query<(Entity, &mut ActivePowerups), With<Player>>,
+    powerups: Query<(Entity, &PowerupComponent), (With<Collider>, Without<Player>)>,
+) {
+    for (power_entity, powerup) in powerups.iter() {
+        let collector = players.iter_mut().find(|(player_entity, _)| {
+            rapier_context.intersection_pair(power_entity, *player_entity) == Some(true)
+        });
+        if let Some((_, mut active)) = collector {
+            active.add(powerup.clone());
             commands.entity(power_entity).despawn_recursive();
         }
     }
 }
+
+fn init_hud(mut commands: Commands) {
+    commands
+        .spawn(TextBundle::default().with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            right: Val::Px(10.0),
+            ..default()
+        }))
+        .insert(PowerupHud {});
+}
+
+fn destroy_hud(mut commands: Commands, huds: Query<Entity, With<PowerupHud>>) {
+    for hud in huds.iter() {
+        commands.entity(hud).despawn_recursive();
+    }
+}
+
+// Uncollected pickups and gems shouldn't carry over into the next game
+#[allow(clippy::type_complexity)]
+fn destroy_pickups(
+    mut commands: Commands,
+    pickups: Query<Entity, Or<(With<PowerupComponent>, With<ScoreGem>)>>,
+) {
+    for pickup in pickups.iter() {
+        commands.entity(pickup).despawn_recursive();
+    }
+}
+
+// Lists the active powerups and how long they have left
+fn update_hud(
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+    players: Query<&ActivePowerups, With<Player>>,
+    mut huds: Query<&mut Text, With<PowerupHud>>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 25.0,
+        color: theme.text,
+    };
+    let sections: Vec<TextSection> = players
+        .iter()
+        .flat_map(|active| active.powerups.iter())
+        .map(|powerup| {
+            TextSection::new(
+                format!(
+                    "{} {:.0}s\n",
+                    powerup.powerup.name(),
+                    powerup.time_left.ceil()
+                ),
+                text_style.clone(),
+            )
+        })
+        .collect();
+
+    for mut text in huds.iter_mut() {
+        text.sections = sections.clone();
+    }
+}
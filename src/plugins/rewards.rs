@@ -0,0 +1,245 @@
+use bevy::{
+    prelude::{
+        default, in_state, Added, App, AssetServer, BuildChildren, Button, Changed, Commands,
+        Component, DespawnRecursiveExt, Entity, EventReader, IntoSystemConfigs, NodeBundle, OnExit,
+        Plugin, Query, Res, ResMut, Resource, TextBundle, Update, With,
+    },
+    text::TextStyle,
+    ui::{
+        AlignItems, BackgroundColor, FlexDirection, Interaction, JustifyContent, PositionType,
+        Style, UiRect, Val,
+    },
+};
+
+use crate::{
+    combat::{Bullet, Damageable, PlayfieldBounds},
+    plugins::{
+        enemy_wave_plugin::{spawn_wave, EnemyAIState, NewWaveEvent},
+        main_menu::spawn_button,
+    },
+    rng::GameRng,
+    state::GameState,
+    theme::{Theme, ThemedUi},
+    GameResources, Player,
+};
+
+// Chance that clearing a wave offers a reward before the next one starts
+pub const REWARD_CHANCE: f64 = 0.5;
+const BONUS_POINTS: u32 = 500;
+const BONUS_DAMAGE: u32 = 1;
+
+// While active, the next wave is held back until the player has picked a reward or skipped it
+#[derive(Resource, Default)]
+pub struct RewardOffer {
+    pub active: bool,
+}
+
+// Extra damage for the player's bullets until the current wave is cleared
+#[derive(Component)]
+struct DamageBoost {
+    bonus: u32,
+}
+
+#[derive(Component)]
+pub struct RewardUiRoot {}
+
+#[derive(Component, Clone, Copy)]
+pub enum RewardButton {
+    Heal,
+    ExtraDamage,
+    BonusPoints,
+    Skip,
+}
+
+pub struct RewardPlugin;
+
+impl Plugin for RewardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RewardOffer>()
+            .add_systems(OnExit(GameState::Game), destroy_reward_offer)
+            .add_systems(
+                Update,
+                (
+                    show_reward_offer,
+                    update_reward_buttons,
+                    boost_player_bullets,
+                    expire_damage_boost,
+                )
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
+
+pub fn reward_offer_active(offer: Res<RewardOffer>) -> bool {
+    offer.active
+}
+
+fn show_reward_offer(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+    offer: Res<RewardOffer>,
+    roots: Query<Entity, With<RewardUiRoot>>,
+) {
+    if !offer.active || !roots.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(
+                    TextBundle::from_section(
+                        "Wave cleared! Pick a reward",
+                        TextStyle {
+                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                            font_size: 40.0,
+                            color: theme.text,
+                        },
+                    )
+                    .with_style(Style {
+                        margin: UiRect::all(Val::Px(10.0)),
+                        ..default()
+                    }),
+                )
+                .insert(ThemedUi {});
+            spawn_button(
+                parent,
+                &asset_server,
+                &theme,
+                "Heal to full",
+                RewardButton::Heal,
+            );
+            spawn_button(
+                parent,
+                &asset_server,
+                &theme,
+                "Extra damage this wave",
+                RewardButton::ExtraDamage,
+            );
+            spawn_button(
+                parent,
+                &asset_server,
+                &theme,
+                &format!("{} bonus points", BONUS_POINTS),
+                RewardButton::BonusPoints,
+            );
+            spawn_button(parent, &asset_server, &theme, "Skip", RewardButton::Skip);
+        })
+        .insert(RewardUiRoot {});
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn update_reward_buttons(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+    bounds: Res<PlayfieldBounds>,
+    ai_state: Res<EnemyAIState>,
+    mut game: ResMut<GameResources>,
+    mut offer: ResMut<RewardOffer>,
+    mut rng: ResMut<GameRng>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &RewardButton),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut players: Query<(Entity, &mut Damageable), With<Player>>,
+    roots: Query<Entity, With<RewardUiRoot>>,
+) {
+    let mut chosen = None;
+    for (interaction, mut color, button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = theme.button_pressed.into();
+                chosen = Some(*button);
+            }
+            _ => {
+                *color = theme.button.into();
+            }
+        }
+    }
+    let Some(reward) = chosen else {
+        return;
+    };
+
+    match reward {
+        RewardButton::Heal => {
+            for (_, mut damageable) in players.iter_mut() {
+                damageable.health = damageable.max_health;
+            }
+        }
+        RewardButton::ExtraDamage => {
+            for (player, _) in players.iter() {
+                commands.entity(player).insert(DamageBoost {
+                    bonus: BONUS_DAMAGE,
+                });
+            }
+        }
+        RewardButton::BonusPoints => game.score += BONUS_POINTS,
+        RewardButton::Skip => {}
+    }
+
+    for root in roots.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+    offer.active = false;
+    spawn_wave(
+        ai_state.current_wave as usize,
+        &bounds,
+        &mut rng,
+        commands,
+        asset_server,
+    );
+}
+
+fn boost_player_bullets(
+    boosts: Query<&DamageBoost, With<Player>>,
+    mut bullets: Query<&mut Bullet, Added<Bullet>>,
+) {
+    // Every ship gets the same boost, so any of them will do
+    let Some(boost) = boosts.iter().next() else {
+        return;
+    };
+    for mut bullet in bullets.iter_mut() {
+        if bullet.is_player_bullet {
+            bullet.damage += boost.bonus;
+        }
+    }
+}
+
+// A new wave event is sent when the boosted wave has been cleared
+fn expire_damage_boost(
+    mut commands: Commands,
+    mut er: EventReader<NewWaveEvent>,
+    players: Query<Entity, With<DamageBoost>>,
+) {
+    if er.iter().count() == 0 {
+        return;
+    }
+    for player in players.iter() {
+        commands.entity(player).remove::<DamageBoost>();
+    }
+}
+
+fn destroy_reward_offer(
+    mut commands: Commands,
+    mut offer: ResMut<RewardOffer>,
+    roots: Query<Entity, With<RewardUiRoot>>,
+) {
+    offer.active = false;
+    for root in roots.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+}
@@ -0,0 +1,193 @@
+use bevy::{
+    prelude::{
+        in_state, shape, Added, App, Assets, BuildChildren, Color, Commands, Component,
+        DespawnRecursiveExt, Entity, IntoSystemConfigs, Mesh, OnExit, PbrBundle, Plugin, Quat,
+        Query, Res, ResMut, StandardMaterial, Transform, Update, Vec3, With,
+    },
+    time::Time,
+};
+
+use crate::{
+    combat::{capped_delta_seconds, Damageable},
+    countdown::StartCountdown,
+    enemy::Enemy,
+    settings::Settings,
+    state::GameState,
+};
+
+const SUPPORT_HEAL_INTERVAL_S: f32 = 3.0;
+const SUPPORT_HEAL_RADIUS: f32 = 3.0;
+const SUPPORT_HEAL_AMOUNT: u32 = 1;
+const HEAL_COLOR: Color = Color::rgb_linear(0.5, 8.0, 2.0);
+const HEAL_BEAM_WIDTH: f32 = 0.04;
+const HEAL_BEAM_DURATION_S: f32 = 0.3;
+
+// An enemy that patches up damaged ships around it every so often, making it worth taking out first
+#[derive(Component)]
+pub struct Support {
+    pub radius: f32,
+    pub heal_amount: u32,
+    pub timer: f32,
+}
+
+impl Default for Support {
+    fn default() -> Self {
+        Self {
+            radius: SUPPORT_HEAL_RADIUS,
+            heal_amount: SUPPORT_HEAL_AMOUNT,
+            timer: SUPPORT_HEAL_INTERVAL_S,
+        }
+    }
+}
+
+// Drawn from the support to each ship it heals, thinning out until it disappears
+#[derive(Component)]
+struct HealBeam {
+    timer: f32,
+}
+
+pub struct SupportPlugin;
+
+impl Plugin for SupportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnExit(GameState::Game), destroy_heal_beams)
+            .add_systems(
+                Update,
+                (mark_supports, heal_nearby_enemies, fade_heal_beams)
+                    .chain()
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
+
+// A green light on top of the ship so supports can be picked out of the formation
+fn mark_supports(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    supports: Query<Entity, Added<Support>>,
+) {
+    for support in supports.iter() {
+        commands.entity(support).with_children(|children| {
+            children.spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::UVSphere {
+                    radius: 0.08,
+                    ..Default::default()
+                })),
+                material: materials.add(StandardMaterial {
+                    emissive: HEAL_COLOR,
+                    ..Default::default()
+                }),
+                transform: Transform::from_xyz(0.0, 0.3, 0.0),
+                ..Default::default()
+            });
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn heal_nearby_enemies(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    countdown: Option<Res<StartCountdown>>,
+    mut supports: Query<(Entity, &mut Support, &Transform)>,
+    mut enemies: Query<(Entity, &mut Damageable, &Transform), With<Enemy>>,
+) {
+    if countdown.is_some() {
+        return;
+    }
+
+    let delta_time = capped_delta_seconds(&time) * settings.game_speed.0;
+    for (support_entity, mut support, support_transform) in supports.iter_mut() {
+        support.timer -= delta_time;
+        if support.timer > 0.0 {
+            continue;
+        }
+        support.timer = SUPPORT_HEAL_INTERVAL_S;
+
+        // Supports don't heal themselves, but two of them can heal each other
+        for (enemy_entity, mut damageable, transform) in enemies.iter_mut() {
+            let in_range = transform
+                .translation
+                .distance(support_transform.translation)
+                <= support.radius;
+            if enemy_entity == support_entity
+                || !in_range
+                || damageable.health == 0
+                || damageable.health >= damageable.max_health
+            {
+                continue;
+            }
+
+            damageable.health =
+                (damageable.health + support.heal_amount).min(damageable.max_health);
+            spawn_heal_beam(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                support_transform.translation,
+                transform.translation,
+            );
+        }
+    }
+}
+
+fn spawn_heal_beam(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    from: Vec3,
+    to: Vec3,
+) {
+    let offset = to - from;
+    // Ships on top of each other have no gap to draw a beam across
+    let Some(direction) = offset.try_normalize() else {
+        return;
+    };
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(
+                HEAL_BEAM_WIDTH,
+                HEAL_BEAM_WIDTH,
+                1.0,
+            ))),
+            material: materials.add(StandardMaterial {
+                emissive: HEAL_COLOR,
+                ..Default::default()
+            }),
+            // The unit long box is stretched along z to span the gap between the two ships
+            transform: Transform::from_translation(from + offset / 2.0)
+                .with_rotation(Quat::from_rotation_arc(Vec3::Z, direction))
+                .with_scale(Vec3::new(1.0, 1.0, offset.length())),
+            ..Default::default()
+        })
+        .insert(HealBeam {
+            timer: HEAL_BEAM_DURATION_S,
+        });
+}
+
+fn fade_heal_beams(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut beams: Query<(Entity, &mut HealBeam, &mut Transform)>,
+) {
+    for (beam_entity, mut beam, mut transform) in beams.iter_mut() {
+        beam.timer -= time.delta_seconds();
+        if beam.timer <= 0.0 {
+            commands.entity(beam_entity).despawn_recursive();
+            continue;
+        }
+        let thickness = beam.timer / HEAL_BEAM_DURATION_S;
+        transform.scale.x = thickness;
+        transform.scale.y = thickness;
+    }
+}
+
+fn destroy_heal_beams(mut commands: Commands, beams: Query<Entity, With<HealBeam>>) {
+    for beam in beams.iter() {
+        commands.entity(beam).despawn_recursive();
+    }
+}
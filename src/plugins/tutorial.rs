@@ -0,0 +1,228 @@
+use bevy::{
+    prelude::{
+        default, in_state, App, AssetServer, BuildChildren, Commands, Component,
+        DespawnRecursiveExt, Entity, Input, IntoSystemConfigs, KeyCode, NodeBundle, OnEnter,
+        OnExit, Plugin, Query, Res, ResMut, Resource, TextBundle, Update, With,
+    },
+    text::TextStyle,
+    time::Time,
+    ui::{AlignItems, FlexDirection, JustifyContent, PositionType, Style, Val},
+};
+
+use crate::{
+    combat::PlayfieldBounds,
+    controls::KeyBindings,
+    enemy::Enemy,
+    plugins::enemy_wave_plugin::{spawn_wave, EnemyAIState},
+    rng::GameRng,
+    settings::Settings,
+    state::GameState,
+    theme::{Theme, ThemedUi},
+};
+
+// How long the player has to dodge the practice enemy's bullets before being asked to destroy it
+const TUTORIAL_DODGE_DURATION_S: f32 = 5.0;
+const TUTORIAL_SKIP_KEY: KeyCode = KeyCode::Return;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TutorialStep {
+    Move,
+    Shoot,
+    Dodge,
+    Destroy,
+}
+
+// Runs in place of the first wave until every step is done or the tutorial is skipped
+#[derive(Resource, Default)]
+pub struct Tutorial {
+    step: Option<TutorialStep>,
+    dodge_timer: f32,
+}
+
+#[derive(Component)]
+struct TutorialPrompt {
+    step: TutorialStep,
+}
+
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tutorial>()
+            .add_systems(OnEnter(GameState::Game), start_tutorial)
+            .add_systems(OnExit(GameState::Game), end_tutorial)
+            .add_systems(
+                Update,
+                (update_tutorial, update_prompt)
+                    .chain()
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
+
+impl TutorialStep {
+    fn prompt(&self, bindings: &KeyBindings) -> String {
+        match self {
+            TutorialStep::Move => format!(
+                "Use {:?}/{:?} to move",
+                bindings.move_left, bindings.move_right
+            ),
+            TutorialStep::Shoot => format!("Press {:?} to shoot", bindings.fire),
+            TutorialStep::Dodge => "Dodge the enemy bullets".to_string(),
+            TutorialStep::Destroy => "Now destroy the enemy".to_string(),
+        }
+    }
+}
+
+impl Tutorial {
+    pub fn is_active(&self) -> bool {
+        self.step.is_some()
+    }
+
+    // The practice enemy only starts firing once the player knows how to move and shoot
+    pub fn holds_fire(&self) -> bool {
+        matches!(
+            self.step,
+            Some(TutorialStep::Move) | Some(TutorialStep::Shoot)
+        )
+    }
+}
+
+// Only the very first run starts with the tutorial, continued runs never do
+pub fn start_tutorial(
+    settings: Res<Settings>,
+    ai_state: Res<EnemyAIState>,
+    mut tutorial: ResMut<Tutorial>,
+) {
+    *tutorial = Tutorial::default();
+    if !settings.tutorial_completed && ai_state.current_wave == 0 {
+        tutorial.step = Some(TutorialStep::Move);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_tutorial(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    time: Res<Time>,
+    bounds: Res<PlayfieldBounds>,
+    mut tutorial: ResMut<Tutorial>,
+    mut settings: ResMut<Settings>,
+    mut rng: ResMut<GameRng>,
+    enemies: Query<Entity, With<Enemy>>,
+) {
+    let Some(step) = tutorial.step else {
+        return;
+    };
+
+    let finished = match step {
+        _ if input.just_pressed(TUTORIAL_SKIP_KEY) => true,
+        TutorialStep::Move => {
+            if input.pressed(bindings.move_left) || input.pressed(bindings.move_right) {
+                tutorial.step = Some(TutorialStep::Shoot);
+            }
+            false
+        }
+        TutorialStep::Shoot => {
+            if input.just_pressed(bindings.fire) {
+                tutorial.step = Some(TutorialStep::Dodge);
+                tutorial.dodge_timer = TUTORIAL_DODGE_DURATION_S;
+            }
+            false
+        }
+        TutorialStep::Dodge => {
+            tutorial.dodge_timer -= time.delta_seconds();
+            if tutorial.dodge_timer <= 0.0 {
+                tutorial.step = Some(TutorialStep::Destroy);
+            }
+            false
+        }
+        TutorialStep::Destroy => enemies.is_empty(),
+    };
+    if !finished {
+        return;
+    }
+
+    // Clear out the practice enemy if the tutorial was skipped and start the real first wave
+    for enemy in enemies.iter() {
+        commands.entity(enemy).despawn_recursive();
+    }
+    tutorial.step = None;
+    settings.tutorial_completed = true;
+    rng.start_wave(0);
+    spawn_wave(0, &bounds, &mut rng, commands, asset_server);
+}
+
+fn update_prompt(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    bindings: Res<KeyBindings>,
+    theme: Res<Theme>,
+    tutorial: Res<Tutorial>,
+    prompts: Query<(Entity, &TutorialPrompt)>,
+) {
+    // Only rebuild the prompt when the step has changed
+    if prompts
+        .iter()
+        .any(|(_, prompt)| Some(prompt.step) == tutorial.step)
+    {
+        return;
+    }
+    for (prompt, _) in prompts.iter() {
+        commands.entity(prompt).despawn_recursive();
+    }
+    let Some(step) = tutorial.step else {
+        return;
+    };
+
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(150.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section(
+                    step.prompt(&bindings),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 40.0,
+                        color: theme.text,
+                    },
+                ))
+                .insert(ThemedUi {});
+            parent
+                .spawn(TextBundle::from_section(
+                    format!("Press {:?} to skip the tutorial", TUTORIAL_SKIP_KEY),
+                    TextStyle {
+                        font,
+                        font_size: 20.0,
+                        color: theme.text,
+                    },
+                ))
+                .insert(ThemedUi {});
+        })
+        .insert(TutorialPrompt { step });
+}
+
+fn end_tutorial(
+    mut commands: Commands,
+    mut tutorial: ResMut<Tutorial>,
+    prompts: Query<Entity, With<TutorialPrompt>>,
+) {
+    tutorial.step = None;
+    for prompt in prompts.iter() {
+        commands.entity(prompt).despawn_recursive();
+    }
+}
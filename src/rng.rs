@@ -0,0 +1,146 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::Resource;
+use rand::{rngs::StdRng, SeedableRng};
+
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+// Mixed into the seed of the drops, so they don't just repeat the rolls of the wave layout
+const DROP_STREAM: u64 = 0xD1CE_D1CE_D1CE_D1CE;
+
+// The randomness that decides what a run looks like: the wave layouts, the rewards between
+// waves and the drops. Both streams are reseeded from the run's seed at the start of every wave,
+// so runs started from the same seed get the same waves, rewards and drops however the frames
+// fell in between. Rolls that depend on frame timing, like when enemies fire, and purely visual
+// randomness use `rand::thread_rng` instead
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    waves: StdRng,
+    drops: StdRng,
+    // Set for daily challenge runs, in days since the unix epoch
+    pub daily_day: Option<u64>,
+}
+
+impl GameRng {
+    pub fn start_random_run(&mut self) {
+        self.start_run(rand::random(), None);
+    }
+
+    pub fn start_daily_challenge(&mut self) {
+        let day = current_day();
+        self.start_run(daily_seed(day), Some(day));
+    }
+
+    fn start_run(&mut self, seed: u64, daily_day: Option<u64>) {
+        self.seed = seed;
+        self.daily_day = daily_day;
+        self.start_wave(0);
+    }
+
+    // Called before anything of the wave is rolled, including the reward offered before it
+    pub fn start_wave(&mut self, wave: u32) {
+        let seed = self.seed ^ wave as u64;
+        self.waves = StdRng::seed_from_u64(seed);
+        self.drops = StdRng::seed_from_u64(seed ^ DROP_STREAM);
+    }
+
+    // Enemy placement and cooldowns when a wave spawns, and whether a reward comes before it
+    pub fn waves(&mut self) -> &mut StdRng {
+        &mut self.waves
+    }
+
+    // Powerups and score gems dropped by destroyed enemies
+    pub fn drops(&mut self) -> &mut StdRng {
+        &mut self.drops
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        let mut rng = Self {
+            seed: 0,
+            waves: StdRng::seed_from_u64(0),
+            drops: StdRng::seed_from_u64(0),
+            daily_day: None,
+        };
+        rng.start_random_run();
+        rng
+    }
+}
+
+// Days since the unix epoch in UTC, so the day rolls over at the same moment for everyone
+pub fn current_day() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() / SECONDS_PER_DAY)
+        .unwrap_or(0)
+}
+
+// The daily challenge seed is the UTC day number scrambled by one fixed splitmix64 step. It is
+// the same for every player on a given day and unrelated from one day to the next. Changing it
+// changes every daily challenge, so the daily best scores would no longer be comparable
+pub fn daily_seed(day: u64) -> u64 {
+    let mut z = day.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D1_049B_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::plugins::rewards::REWARD_CHANCE;
+
+    // What a player would see of the first waves of a run: whether a reward comes before each
+    // wave and a few rolls of its layout. Every run kills a different number of enemies, so it
+    // rolls for a different number of drops in between
+    fn play_waves(rng: &mut GameRng, drops_per_wave: usize) -> Vec<(bool, [u32; 3])> {
+        (0..6)
+            .map(|wave| {
+                rng.start_wave(wave);
+                let reward = rng.waves().gen_bool(REWARD_CHANCE);
+                let layout = [(); 3].map(|_| rng.waves().gen_range(0..1000));
+                for _ in 0..drops_per_wave * wave as usize {
+                    rng.drops().gen::<f64>();
+                }
+                (reward, layout)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_waves_and_rewards() {
+        let mut first = GameRng::default();
+        let mut second = GameRng::default();
+        first.start_run(daily_seed(20000), Some(20000));
+        second.start_run(daily_seed(20000), Some(20000));
+
+        assert_eq!(play_waves(&mut first, 0), play_waves(&mut second, 3));
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_drops_in_each_wave() {
+        let mut first = GameRng::default();
+        let mut second = GameRng::default();
+        first.start_run(daily_seed(20000), Some(20000));
+        second.start_run(daily_seed(20000), Some(20000));
+        play_waves(&mut first, 1);
+        play_waves(&mut second, 4);
+
+        first.start_wave(2);
+        second.start_wave(2);
+        assert_eq!(first.drops().gen::<u64>(), second.drops().gen::<u64>());
+    }
+
+    #[test]
+    fn different_days_give_different_waves() {
+        let mut first = GameRng::default();
+        let mut second = GameRng::default();
+        first.start_run(daily_seed(20000), Some(20000));
+        second.start_run(daily_seed(20001), Some(20001));
+
+        assert_ne!(play_waves(&mut first, 0), play_waves(&mut second, 0));
+    }
+}
@@ -0,0 +1,250 @@
+use std::{
+    fs, thread,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    log::{error, warn},
+    prelude::{Commands, DetectChanges, Input, KeyCode, Local, Query, Res, ResMut, Resource},
+    window::{PresentMode, Window},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    camera::CameraView,
+    controls::{ControlSettings, KeyBindings},
+    theme::{Theme, ThemePreset},
+};
+
+const SETTINGS_PATH: &str = "settings.json";
+// Steps for the screen shake option, from off to full strength
+const SHAKE_SCALE_PRESETS: [f32; 5] = [0.0, 0.25, 0.5, 0.75, 1.0];
+
+// All player options, loaded at startup and saved whenever one of them changes
+#[derive(Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub theme: ThemePreset,
+    // Overrides the chosen theme with a palette that doesn't rely on telling reds apart
+    pub color_blind: bool,
+    // The tutorial is only shown until it has been completed or skipped once
+    pub tutorial_completed: bool,
+    pub camera_view: CameraView,
+    pub controls: ControlSettings,
+    pub game_speed: GameSpeed,
+    pub frame_limit: FrameLimit,
+    pub weapon_model: WeaponModel,
+    // Scales the camera shake, between 0.0 (off) and 1.0
+    pub shake_scale: f32,
+    // Turns off motion that is only there for show, for players who get motion sick
+    pub reduced_motion: bool,
+    // Floating numbers for each hit, off by default to keep the screen clean
+    pub damage_numbers: bool,
+    // Stops the game while the controls overlay is open
+    pub pause_on_help: bool,
+}
+
+impl Settings {
+    pub fn active_theme(&self) -> ThemePreset {
+        if self.color_blind {
+            ThemePreset::ColorBlind
+        } else {
+            self.theme
+        }
+    }
+
+    // Steps through the shake presets, wrapping back around to off
+    pub fn next_shake_scale(&self) -> f32 {
+        SHAKE_SCALE_PRESETS
+            .iter()
+            .copied()
+            .find(|scale| *scale > self.shake_scale + f32::EPSILON)
+            .unwrap_or(SHAKE_SCALE_PRESETS[0])
+    }
+
+    pub fn save(&self) {
+        let result = serde_json::to_string_pretty(self)
+            .map_err(|err| err.to_string())
+            .and_then(|json| fs::write(SETTINGS_PATH, json).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            error!("Failed to save the settings: {}", err);
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: ThemePreset::Default,
+            color_blind: false,
+            tutorial_completed: false,
+            camera_view: CameraView::Classic,
+            controls: ControlSettings::default(),
+            game_speed: GameSpeed::default(),
+            frame_limit: FrameLimit::VSync,
+            weapon_model: WeaponModel::Cooldown,
+            shake_scale: 1.0,
+            reduced_motion: false,
+            damage_numbers: false,
+            pause_on_help: true,
+        }
+    }
+}
+
+// Scales how fast the enemies move and fire and how fast their bullets travel, the player's
+// own controls always run at full speed
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct GameSpeed(pub f32);
+
+impl GameSpeed {
+    const PRESETS: [f32; 3] = [0.75, 1.0, 1.25];
+
+    pub fn next(&self) -> GameSpeed {
+        let index = GameSpeed::PRESETS
+            .iter()
+            .position(|speed| *speed == self.0)
+            .map_or(0, |index| (index + 1) % GameSpeed::PRESETS.len());
+        GameSpeed(GameSpeed::PRESETS[index])
+    }
+}
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        GameSpeed(1.0)
+    }
+}
+
+// Cooldown fires at a steady rate forever, heat fires faster but overheats when held too long
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum WeaponModel {
+    Cooldown,
+    Heat,
+}
+
+impl WeaponModel {
+    pub fn next(&self) -> WeaponModel {
+        match self {
+            WeaponModel::Cooldown => WeaponModel::Heat,
+            WeaponModel::Heat => WeaponModel::Cooldown,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeaponModel::Cooldown => "Cooldown",
+            WeaponModel::Heat => "Heat",
+        }
+    }
+}
+
+// Either waits for the display's refresh or caps the frame rate, to keep the GPU from running
+// flat out on laptops
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum FrameLimit {
+    VSync,
+    Fps60,
+    Fps120,
+    Uncapped,
+}
+
+impl FrameLimit {
+    pub fn next(&self) -> FrameLimit {
+        match self {
+            FrameLimit::VSync => FrameLimit::Fps60,
+            FrameLimit::Fps60 => FrameLimit::Fps120,
+            FrameLimit::Fps120 => FrameLimit::Uncapped,
+            FrameLimit::Uncapped => FrameLimit::VSync,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FrameLimit::VSync => "VSync",
+            FrameLimit::Fps60 => "60 FPS",
+            FrameLimit::Fps120 => "120 FPS",
+            FrameLimit::Uncapped => "Uncapped",
+        }
+    }
+
+    fn present_mode(&self) -> PresentMode {
+        match self {
+            FrameLimit::VSync => PresentMode::AutoVsync,
+            FrameLimit::Fps60 | FrameLimit::Fps120 | FrameLimit::Uncapped => {
+                PresentMode::AutoNoVsync
+            }
+        }
+    }
+
+    // Shortest time a frame may take, if the frame rate is capped
+    fn min_frame_time(&self) -> Option<Duration> {
+        match self {
+            FrameLimit::Fps60 => Some(Duration::from_secs_f64(1.0 / 60.0)),
+            FrameLimit::Fps120 => Some(Duration::from_secs_f64(1.0 / 120.0)),
+            FrameLimit::VSync | FrameLimit::Uncapped => None,
+        }
+    }
+}
+
+pub fn load_settings(mut commands: Commands) {
+    // A missing file just means that the defaults haven't been changed yet, and a corrupt one
+    // is replaced with the defaults the next time a setting changes
+    let settings = match fs::read_to_string(SETTINGS_PATH) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|err| {
+            warn!("Failed to parse the settings, using the defaults: {}", err);
+            Settings::default()
+        }),
+        Err(_) => Settings::default(),
+    };
+    commands.insert_resource(settings);
+}
+
+pub fn toggle_color_blind(
+    input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut settings: ResMut<Settings>,
+) {
+    // Works everywhere, so it must not toggle while the key is also used to play
+    let key = bindings.toggle_color_blind;
+    if input.just_pressed(key) && !bindings.is_shared(key) {
+        settings.color_blind = !settings.color_blind;
+    }
+}
+
+pub fn apply_settings(settings: Res<Settings>, mut theme: ResMut<Theme>) {
+    if settings.is_changed() && theme.preset != settings.active_theme() {
+        *theme = Theme::new(settings.active_theme());
+    }
+}
+
+pub fn apply_frame_limit(settings: Res<Settings>, mut windows: Query<&mut Window>) {
+    if !settings.is_changed() {
+        return;
+    }
+    let present_mode = settings.frame_limit.present_mode();
+    for mut window in windows.iter_mut() {
+        if window.present_mode != present_mode {
+            window.present_mode = present_mode;
+        }
+    }
+}
+
+// Runs last in the frame and sleeps off whatever is left of the capped frame time. Gameplay
+// scales everything by the frame's delta time, so the cap only changes how smooth it looks
+pub fn limit_frame_rate(settings: Res<Settings>, mut frame_start: Local<Option<Instant>>) {
+    if let (Some(min_frame_time), Some(start)) =
+        (settings.frame_limit.min_frame_time(), *frame_start)
+    {
+        let elapsed = start.elapsed();
+        if elapsed < min_frame_time {
+            thread::sleep(min_frame_time - elapsed);
+        }
+    }
+    *frame_start = Some(Instant::now());
+}
+
+pub fn save_settings_on_change(settings: Res<Settings>) {
+    // Loading the settings counts as a change, but there is nothing new to write yet
+    if settings.is_changed() && !settings.is_added() {
+        settings.save();
+    }
+}
@@ -1,8 +1,221 @@
-use bevy::prelude::States;
+use std::any::type_name;
 
+use bevy::{
+    log::warn,
+    prelude::{NextState, Resource, State, States, World},
+};
+
+use crate::{
+    controls::KeyBindings,
+    plugins::{
+        achievements::Achievements, enemy_wave_plugin::EnemyAIState, leaderboard::Leaderboard,
+    },
+    rng::GameRng,
+    settings::Settings,
+    theme::Theme,
+    GameResources,
+};
+
+// The valid transitions are:
+// - Menu -> Game, from any of the play buttons
+// - Menu -> Leaderboard
+// - Menu -> Options
+// - Game -> GameOver, once every ship has been destroyed
+// - Game -> Menu, when quitting or after clearing the last wave
+// - GameOver -> Game, when restarting or continuing
+// - GameOver -> Menu
+// - Leaderboard -> Menu
+// - Options -> Menu
+// Rewards and the tutorial are overlays within Game rather than states of their own
 #[derive(States, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
 pub enum GameState {
     #[default]
     Menu,
     Game,
+    GameOver,
+    Leaderboard,
+    // The settings, kept off the main menu so that it fits on the screen
+    Options,
+}
+
+impl GameState {
+    pub fn can_transition_to(&self, next: GameState) -> bool {
+        matches!(
+            (self, next),
+            (GameState::Menu, GameState::Game)
+                | (GameState::Menu, GameState::Leaderboard)
+                | (GameState::Menu, GameState::Options)
+                | (GameState::Game, GameState::GameOver)
+                | (GameState::Game, GameState::Menu)
+                | (GameState::GameOver, GameState::Game)
+                | (GameState::GameOver, GameState::Menu)
+                | (GameState::Leaderboard, GameState::Menu)
+                | (GameState::Options, GameState::Menu)
+        )
+    }
+}
+
+// Runs right before a queued transition is applied. Transitions that aren't listed above are
+// dropped, and anything the next state relies on that is missing is filled in with its defaults
+// so that its setup doesn't panic
+pub fn guard_state_transitions(world: &mut World) {
+    let current = *world.resource::<State<GameState>>().get();
+    let Some(next) = world.resource::<NextState<GameState>>().0 else {
+        return;
+    };
+
+    if !current.can_transition_to(next) {
+        warn!(
+            "Ignoring invalid state transition from {:?} to {:?}",
+            current, next
+        );
+        world.resource_mut::<NextState<GameState>>().0 = None;
+        return;
+    }
+
+    ensure_resource::<Settings>(world, next);
+    ensure_resource::<Theme>(world, next);
+    ensure_resource::<GameResources>(world, next);
+    match next {
+        GameState::Menu => {
+            ensure_resource::<EnemyAIState>(world, next);
+            ensure_resource::<GameRng>(world, next);
+        }
+        GameState::Game => {
+            ensure_resource::<KeyBindings>(world, next);
+            ensure_resource::<EnemyAIState>(world, next);
+            ensure_resource::<GameRng>(world, next);
+            ensure_resource::<Achievements>(world, next);
+        }
+        GameState::GameOver => ensure_resource::<EnemyAIState>(world, next),
+        GameState::Leaderboard => ensure_resource::<Leaderboard>(world, next),
+        GameState::Options => {}
+    }
+}
+
+fn ensure_resource<R: Resource + Default>(world: &mut World, state: GameState) {
+    if world.contains_resource::<R>() {
+        return;
+    }
+    warn!(
+        "Entering {:?} without {}, using the defaults",
+        state,
+        type_name::<R>()
+    );
+    world.insert_resource(R::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::{
+        apply_state_transition, App, IntoSystemConfigs, MinimalPlugins, NextState, State,
+        StateTransition,
+    };
+
+    use super::{guard_state_transitions, GameState};
+    use crate::plugins::leaderboard::Leaderboard;
+
+    const STATES: [GameState; 5] = [
+        GameState::Menu,
+        GameState::Game,
+        GameState::GameOver,
+        GameState::Leaderboard,
+        GameState::Options,
+    ];
+
+    const ALLOWED: [(GameState, GameState); 9] = [
+        (GameState::Menu, GameState::Game),
+        (GameState::Menu, GameState::Leaderboard),
+        (GameState::Menu, GameState::Options),
+        (GameState::Game, GameState::GameOver),
+        (GameState::Game, GameState::Menu),
+        (GameState::GameOver, GameState::Game),
+        (GameState::GameOver, GameState::Menu),
+        (GameState::Leaderboard, GameState::Menu),
+        (GameState::Options, GameState::Menu),
+    ];
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_state::<GameState>()
+            .add_systems(
+                StateTransition,
+                guard_state_transitions.before(apply_state_transition::<GameState>),
+            );
+        app.update();
+        app
+    }
+
+    fn request(app: &mut App, next: GameState) {
+        app.world.resource_mut::<NextState<GameState>>().set(next);
+        app.update();
+    }
+
+    fn current(app: &App) -> GameState {
+        *app.world.resource::<State<GameState>>().get()
+    }
+
+    #[test]
+    fn only_listed_transitions_are_allowed() {
+        for from in STATES {
+            for to in STATES {
+                assert_eq!(
+                    from.can_transition_to(to),
+                    ALLOWED.contains(&(from, to)),
+                    "{:?} -> {:?}",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn steps_through_every_allowed_transition() {
+        let mut app = app();
+        let path = [
+            GameState::Leaderboard,
+            GameState::Menu,
+            GameState::Options,
+            GameState::Menu,
+            GameState::Game,
+            GameState::GameOver,
+            GameState::Game,
+            GameState::Menu,
+            GameState::Game,
+            GameState::GameOver,
+            GameState::Menu,
+        ];
+        for next in path {
+            request(&mut app, next);
+            assert_eq!(current(&app), next);
+        }
+    }
+
+    #[test]
+    fn invalid_transitions_are_dropped() {
+        let mut app = app();
+        for next in [GameState::GameOver, GameState::Menu] {
+            request(&mut app, next);
+            assert_eq!(current(&app), GameState::Menu);
+            assert!(app.world.resource::<NextState<GameState>>().0.is_none());
+        }
+
+        request(&mut app, GameState::Leaderboard);
+        for next in [GameState::Game, GameState::GameOver] {
+            request(&mut app, next);
+            assert_eq!(current(&app), GameState::Leaderboard);
+        }
+    }
+
+    #[test]
+    fn missing_resources_get_their_defaults() {
+        let mut app = app();
+        assert!(!app.world.contains_resource::<Leaderboard>());
+
+        request(&mut app, GameState::Leaderboard);
+        assert_eq!(current(&app), GameState::Leaderboard);
+        assert!(app.world.contains_resource::<Leaderboard>());
+    }
 }
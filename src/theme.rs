@@ -0,0 +1,162 @@
+use bevy::{
+    prelude::{
+        Assets, Button, Children, Color, Component, DetectChanges, Handle, Query, Res, ResMut,
+        Resource, StandardMaterial, With, Without,
+    },
+    text::Text,
+    ui::{BackgroundColor, BorderColor},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::combat::{bullet_trail_material, Bullet, BulletTrail, BulletTrailAssets};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ThemePreset {
+    Default,
+    HighContrast,
+    ColorBlind,
+}
+
+// Colors shared by the UI and spawned entities, swapped out as a whole when changing preset
+#[derive(Resource, Clone)]
+pub struct Theme {
+    pub preset: ThemePreset,
+    pub button: Color,
+    pub button_pressed: Color,
+    // Highlights the button selected with the keyboard or a gamepad
+    pub button_focused: Color,
+    pub button_border: Color,
+    pub text: Color,
+    // Bullet colors are emissive, hence the values far above 1.0
+    pub player_bullet: Color,
+    pub enemy_bullet: Color,
+}
+
+impl ThemePreset {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThemePreset::Default => "Default",
+            ThemePreset::HighContrast => "High contrast",
+            ThemePreset::ColorBlind => "Color-blind",
+        }
+    }
+
+    // Cycles through the regular presets, the color-blind palette is toggled separately
+    pub fn next(&self) -> ThemePreset {
+        match self {
+            ThemePreset::Default => ThemePreset::HighContrast,
+            ThemePreset::HighContrast | ThemePreset::ColorBlind => ThemePreset::Default,
+        }
+    }
+}
+
+impl Theme {
+    pub fn new(preset: ThemePreset) -> Self {
+        match preset {
+            ThemePreset::Default => Self {
+                preset,
+                button: Color::rgb(0.15, 0.15, 0.15),
+                button_pressed: Color::rgb(0.4, 0.7, 0.4),
+                button_focused: Color::rgb(0.3, 0.3, 0.3),
+                button_border: Color::BLACK,
+                text: Color::rgb(0.9, 0.9, 0.9),
+                player_bullet: Color::rgb_linear(35.0, 1.0, 2.0),
+                enemy_bullet: Color::rgb_linear(35.0, 1.0, 2.0),
+            },
+            ThemePreset::HighContrast => Self {
+                preset,
+                button: Color::BLACK,
+                button_pressed: Color::rgb(1.0, 0.85, 0.0),
+                button_focused: Color::rgb(0.0, 0.3, 0.6),
+                button_border: Color::WHITE,
+                text: Color::WHITE,
+                player_bullet: Color::rgb_linear(2.0, 20.0, 35.0),
+                enemy_bullet: Color::rgb_linear(35.0, 8.0, 0.0),
+            },
+            // Blue against yellow stays distinguishable for the common forms of color blindness
+            ThemePreset::ColorBlind => Self {
+                preset,
+                button: Color::rgb(0.15, 0.15, 0.15),
+                button_pressed: Color::rgb(0.0, 0.45, 0.7),
+                button_focused: Color::rgb(0.35, 0.35, 0.35),
+                button_border: Color::BLACK,
+                text: Color::rgb(0.9, 0.9, 0.9),
+                player_bullet: Color::rgb_linear(0.0, 8.0, 35.0),
+                enemy_bullet: Color::rgb_linear(35.0, 30.0, 0.0),
+            },
+        }
+    }
+
+    pub fn bullet(&self, is_player_bullet: bool) -> Color {
+        if is_player_bullet {
+            self.player_bullet
+        } else {
+            self.enemy_bullet
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::new(ThemePreset::Default)
+    }
+}
+
+// Tags UI that takes its colors from the theme, anything else keeps its own colors when the
+// theme changes
+#[derive(Component)]
+pub struct ThemedUi {}
+
+// Recolors UI that is already on screen when the theme changes
+#[allow(clippy::type_complexity)]
+pub fn apply_theme_to_ui(
+    theme: Res<Theme>,
+    mut buttons: Query<(&mut BackgroundColor, &mut BorderColor), (With<Button>, With<ThemedUi>)>,
+    mut texts: Query<&mut Text, With<ThemedUi>>,
+) {
+    if !theme.is_changed() || theme.is_added() {
+        return;
+    }
+
+    for (mut color, mut border) in buttons.iter_mut() {
+        *color = theme.button.into();
+        *border = BorderColor(theme.button_border);
+    }
+    for mut text in texts.iter_mut() {
+        for section in text.sections.iter_mut() {
+            section.style.color = theme.text;
+        }
+    }
+}
+
+// Bullets get their own material when spawned, so recolor the ones already in flight. Trails
+// share one material per side, recoloring those updates every trail at once
+pub fn apply_theme_to_bullets(
+    theme: Res<Theme>,
+    trail: Res<BulletTrailAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    bullets: Query<(&Bullet, &Children)>,
+    bullet_meshes: Query<&Handle<StandardMaterial>, Without<BulletTrail>>,
+) {
+    if !theme.is_changed() {
+        return;
+    }
+
+    for is_player_bullet in [true, false] {
+        if let Some(material) = materials.get_mut(trail.material(is_player_bullet)) {
+            *material = bullet_trail_material(&theme, is_player_bullet);
+        }
+    }
+
+    for (bullet, children) in bullets.iter() {
+        for child in children.iter() {
+            let Ok(handle) = bullet_meshes.get(*child) else {
+                continue;
+            };
+            if let Some(material) = materials.get_mut(handle) {
+                material.emissive = theme.bullet(bullet.is_player_bullet);
+            }
+        }
+    }
+}